@@ -0,0 +1,104 @@
+use super::Lab;
+
+pub struct DE1976;
+
+impl DE1976 {
+    /// Returns the CIE76 difference between two `Lab` colors, which is their
+    /// euclidean distance.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate delta_e;
+    /// extern crate lab;
+    ///
+    /// use delta_e::DE1976;
+    /// use lab::Lab;
+    ///
+    /// fn main() {
+    ///     let color_1 = Lab {
+    ///         l: 38.972,
+    ///         a: 58.991,
+    ///         b: 37.138,
+    ///     };
+    ///
+    ///     let color_2 = Lab {
+    ///         l: 54.528,
+    ///         a: 42.416,
+    ///         b: 54.497,
+    ///     };
+    ///
+    ///     let delta_e = DE1976::new(color_1, color_2);
+    ///     println!("The color difference is: {}", delta_e);
+    /// }
+    /// ```
+
+    pub fn new(color_1: Lab, color_2: Lab) -> f32 {
+        ((color_1.l - color_2.l).powi(2) + (color_1.a - color_2.a).powi(2) + (color_1.b - color_2.b).powi(2)).sqrt()
+    }
+
+    /// Returns the CIE76 difference between two RGB colors.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate delta_e;
+    ///
+    /// use delta_e::DE1976;
+    ///
+    /// fn main() {
+    ///     let color_1 = [234, 76, 76];
+    ///     let color_2 = [76, 187, 234];
+    ///
+    ///     let delta_e = DE1976::from_rgb(&color_1, &color_2);
+    ///     println!("The color difference is: {}", delta_e);
+    /// }
+    /// ```
+
+    pub fn from_rgb(color_1: &[u8; 3], color_2: &[u8; 3]) -> f32 {
+        let lab_1 = Lab::from_rgb(color_1);
+        let lab_2 = Lab::from_rgb(color_2);
+
+        DE1976::new(lab_1, lab_2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DE1976, Lab};
+
+    fn round(val: f32) -> f32 {
+        let rounded = val * 10000 as f32;
+        rounded.round() / 10000 as f32
+    }
+
+    fn assert_delta_e(expected: f32, lab1: &[f32; 3], lab2: &[f32; 3]) {
+        let color_1 = Lab {
+            l: lab1[0],
+            a: lab1[1],
+            b: lab1[2],
+        };
+
+        let color_2 = Lab {
+            l: lab2[0],
+            a: lab2[1],
+            b: lab2[2],
+        };
+
+        assert_eq!(round(DE1976::new(color_1, color_2)), expected);
+    }
+
+    #[test]
+    fn tests() {
+        assert_delta_e(0.0, &[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]);
+        assert_delta_e(100.0, &[100.0, 0.005, -0.010], &[0.0, 0.0, 0.0]);
+        assert_delta_e(4.0011, &[50.0000, 2.6772, -79.7751], &[50.0000, 0.0000, -82.7485]);
+        assert_delta_e(2.2361, &[50.0000, 0.0000, 0.0000], &[50.0000, -1.0000, 2.0000]);
+        assert_delta_e(36.8680, &[50.0000, 2.5000, 0.0000], &[73.0000, 25.0000, -18.0000]);
+        assert_delta_e(31.9100, &[50.0000, 2.5000, 0.0000], &[61.0000, -5.0000, 29.0000]);
+        assert_delta_e(3.1819, &[60.2574, -34.0099, 36.2677], &[60.4626, -34.1751, 39.4387]);
+        assert_delta_e(6.5847, &[22.7233, 20.0904, -46.6940], &[23.0331, 14.9730, -42.5619]);
+        assert_delta_e(0.9441, &[6.7747, -0.2908, -2.4247], &[5.8714, -0.0985, -2.2286]);
+        assert_delta_e(1.5051, &[90.8027, -2.0831, 1.4410], &[91.1528, -1.6435, 0.0447]);
+    }
+}
@@ -0,0 +1,145 @@
+use super::Lab;
+
+pub struct DE1994;
+
+impl DE1994 {
+    /// Returns the CIE94 difference between two `Lab` colors, using the
+    /// weighting factors for graphic arts. The difference is not symmetric,
+    /// `color_1` is the reference color.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate delta_e;
+    /// extern crate lab;
+    ///
+    /// use delta_e::DE1994;
+    /// use lab::Lab;
+    ///
+    /// fn main() {
+    ///     let color_1 = Lab {
+    ///         l: 38.972,
+    ///         a: 58.991,
+    ///         b: 37.138,
+    ///     };
+    ///
+    ///     let color_2 = Lab {
+    ///         l: 54.528,
+    ///         a: 42.416,
+    ///         b: 54.497,
+    ///     };
+    ///
+    ///     let delta_e = DE1994::new(color_1, color_2);
+    ///     println!("The color difference is: {}", delta_e);
+    /// }
+    /// ```
+
+    pub fn new(color_1: Lab, color_2: Lab) -> f32 {
+        get_delta_e(color_1, color_2, 1.0, 0.045, 0.015)
+    }
+
+    /// Returns the CIE94 difference between two `Lab` colors, using the
+    /// weighting factors for textiles. The difference is not symmetric,
+    /// `color_1` is the reference color.
+    pub fn textiles(color_1: Lab, color_2: Lab) -> f32 {
+        get_delta_e(color_1, color_2, 2.0, 0.048, 0.014)
+    }
+
+    /// Returns the CIE94 difference between two RGB colors, using the
+    /// weighting factors for graphic arts.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate delta_e;
+    ///
+    /// use delta_e::DE1994;
+    ///
+    /// fn main() {
+    ///     let color_1 = [234, 76, 76];
+    ///     let color_2 = [76, 187, 234];
+    ///
+    ///     let delta_e = DE1994::from_rgb(&color_1, &color_2);
+    ///     println!("The color difference is: {}", delta_e);
+    /// }
+    /// ```
+
+    pub fn from_rgb(color_1: &[u8; 3], color_2: &[u8; 3]) -> f32 {
+        let lab_1 = Lab::from_rgb(color_1);
+        let lab_2 = Lab::from_rgb(color_2);
+
+        DE1994::new(lab_1, lab_2)
+    }
+}
+
+fn get_delta_e(color_1: Lab, color_2: Lab, ksub_l: f32, k1: f32, k2: f32) -> f32 {
+    let delta_l = color_1.l - color_2.l;
+
+    let c1 = (color_1.a.powi(2) + color_1.b.powi(2)).sqrt();
+    let c2 = (color_2.a.powi(2) + color_2.b.powi(2)).sqrt();
+
+    let delta_c = c1 - c2;
+
+    // The hue difference follows from the other differences, rounding may
+    // push its square slightly below zero
+    let delta_h_squared = ((color_1.a - color_2.a).powi(2) + (color_1.b - color_2.b).powi(2) - delta_c.powi(2)).max(0.0);
+
+    let s_sub_c = 1.0 + k1 * c1;
+    let s_sub_h = 1.0 + k2 * c1;
+
+    let lightness = delta_l / ksub_l;
+
+    let chroma = delta_c / s_sub_c;
+
+    (lightness.powi(2) + chroma.powi(2) + delta_h_squared / s_sub_h.powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DE1994, Lab};
+
+    fn round(val: f32) -> f32 {
+        let rounded = val * 10000 as f32;
+        rounded.round() / 10000 as f32
+    }
+
+    fn to_lab(lab: &[f32; 3]) -> Lab {
+        Lab {
+            l: lab[0],
+            a: lab[1],
+            b: lab[2],
+        }
+    }
+
+    fn assert_delta_e(expected: f32, lab1: &[f32; 3], lab2: &[f32; 3]) {
+        assert_eq!(round(DE1994::new(to_lab(lab1), to_lab(lab2))), expected);
+    }
+
+    fn assert_delta_e_textiles(expected: f32, lab1: &[f32; 3], lab2: &[f32; 3]) {
+        assert_eq!(round(DE1994::textiles(to_lab(lab1), to_lab(lab2))), expected);
+    }
+
+    #[test]
+    fn tests() {
+        assert_delta_e(0.0, &[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]);
+        assert_delta_e(100.0, &[100.0, 0.005, -0.010], &[0.0, 0.0, 0.0]);
+        assert_delta_e(1.3950, &[50.0000, 2.6772, -79.7751], &[50.0000, 0.0000, -82.7485]);
+        assert_delta_e(2.2361, &[50.0000, 0.0000, 0.0000], &[50.0000, -1.0000, 2.0000]);
+        assert_delta_e(34.6892, &[50.0000, 2.5000, 0.0000], &[73.0000, 25.0000, -18.0000]);
+        assert_delta_e(29.4414, &[50.0000, 2.5000, 0.0000], &[61.0000, -5.0000, 29.0000]);
+        assert_delta_e(1.3910, &[60.2574, -34.0099, 36.2677], &[60.4626, -34.1751, 39.4387]);
+        assert_delta_e(2.5561, &[22.7233, 20.0904, -46.6940], &[23.0331, 14.9730, -42.5619]);
+        assert_delta_e(0.9385, &[6.7747, -0.2908, -2.4247], &[5.8714, -0.0985, -2.2286]);
+        assert_delta_e(1.4195, &[90.8027, -2.0831, 1.4410], &[91.1528, -1.6435, 0.0447]);
+    }
+
+    #[test]
+    fn textiles_tests() {
+        assert_delta_e_textiles(0.0, &[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]);
+        assert_delta_e_textiles(50.0, &[100.0, 0.005, -0.010], &[0.0, 0.0, 0.0]);
+        assert_delta_e_textiles(1.4230, &[50.0000, 2.6772, -79.7751], &[50.0000, 0.0000, -82.7485]);
+        assert_delta_e_textiles(28.2503, &[50.0000, 2.5000, 0.0000], &[73.0000, 25.0000, -18.0000]);
+        assert_delta_e_textiles(2.5310, &[22.7233, 20.0904, -46.6940], &[23.0331, 14.9730, -42.5619]);
+        assert_delta_e_textiles(0.5182, &[6.7747, -0.2908, -2.4247], &[5.8714, -0.0985, -2.2286]);
+    }
+}
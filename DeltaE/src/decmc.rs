@@ -0,0 +1,164 @@
+use super::{f32, Lab};
+
+pub struct DECMC;
+
+impl DECMC {
+    /// Returns the CMC l:c difference between two `Lab` colors. A ratio of
+    /// 2:1 is commonly used for acceptability and 1:1 for perceptibility. The
+    /// difference is not symmetric, `color_1` is the reference color.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate delta_e;
+    /// extern crate lab;
+    ///
+    /// use delta_e::DECMC;
+    /// use lab::Lab;
+    ///
+    /// fn main() {
+    ///     let color_1 = Lab {
+    ///         l: 38.972,
+    ///         a: 58.991,
+    ///         b: 37.138,
+    ///     };
+    ///
+    ///     let color_2 = Lab {
+    ///         l: 54.528,
+    ///         a: 42.416,
+    ///         b: 54.497,
+    ///     };
+    ///
+    ///     let delta_e = DECMC::new(color_1, color_2, 2.0, 1.0);
+    ///     println!("The color difference is: {}", delta_e);
+    /// }
+    /// ```
+
+    pub fn new(color_1: Lab, color_2: Lab, lightness_weight: f32, chroma_weight: f32) -> f32 {
+        let delta_l = color_1.l - color_2.l;
+
+        let c1 = (color_1.a.powi(2) + color_1.b.powi(2)).sqrt();
+        let c2 = (color_2.a.powi(2) + color_2.b.powi(2)).sqrt();
+
+        let delta_c = c1 - c2;
+
+        // The hue difference follows from the other differences, rounding may
+        // push its square slightly below zero
+        let delta_h_squared = ((color_1.a - color_2.a).powi(2) + (color_1.b - color_2.b).powi(2) - delta_c.powi(2)).max(0.0);
+
+        let h1 = get_hue_angle(color_1.b, color_1.a);
+
+        let f = (c1.powi(4) / (c1.powi(4) + 1900.0)).sqrt();
+
+        let t = if (164.0..=345.0).contains(&h1) {
+            0.56 + (0.2 * degrees_to_radians(h1 + 168.0).cos()).abs()
+        } else {
+            0.36 + (0.4 * degrees_to_radians(h1 + 35.0).cos()).abs()
+        };
+
+        let s_sub_l = if color_1.l < 16.0 {
+            0.511
+        } else {
+            0.040975 * color_1.l / (1.0 + 0.01765 * color_1.l)
+        };
+
+        let s_sub_c = 0.0638 * c1 / (1.0 + 0.0131 * c1) + 0.638;
+
+        let s_sub_h = s_sub_c * (f * t + 1.0 - f);
+
+        let lightness = delta_l / (lightness_weight * s_sub_l);
+
+        let chroma = delta_c / (chroma_weight * s_sub_c);
+
+        (lightness.powi(2) + chroma.powi(2) + delta_h_squared / s_sub_h.powi(2)).sqrt()
+    }
+
+    /// Returns the CMC l:c difference between two RGB colors.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate delta_e;
+    ///
+    /// use delta_e::DECMC;
+    ///
+    /// fn main() {
+    ///     let color_1 = [234, 76, 76];
+    ///     let color_2 = [76, 187, 234];
+    ///
+    ///     let delta_e = DECMC::from_rgb(&color_1, &color_2, 2.0, 1.0);
+    ///     println!("The color difference is: {}", delta_e);
+    /// }
+    /// ```
+
+    pub fn from_rgb(color_1: &[u8; 3], color_2: &[u8; 3], lightness_weight: f32, chroma_weight: f32) -> f32 {
+        let lab_1 = Lab::from_rgb(color_1);
+        let lab_2 = Lab::from_rgb(color_2);
+
+        DECMC::new(lab_1, lab_2, lightness_weight, chroma_weight)
+    }
+}
+
+fn get_hue_angle(x: f32, y: f32) -> f32 {
+    let hue_angle = x.atan2(y) * (180.0 / f32::consts::PI);
+
+    if hue_angle < 0.0 {
+        hue_angle + 360.0
+    } else {
+        hue_angle
+    }
+}
+
+fn degrees_to_radians(degrees: f32) -> f32 {
+    degrees * (f32::consts::PI / 180.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DECMC, Lab};
+
+    fn round(val: f32) -> f32 {
+        let rounded = val * 10000 as f32;
+        rounded.round() / 10000 as f32
+    }
+
+    fn assert_delta_e(expected: f32, lightness_weight: f32, lab1: &[f32; 3], lab2: &[f32; 3]) {
+        let color_1 = Lab {
+            l: lab1[0],
+            a: lab1[1],
+            b: lab1[2],
+        };
+
+        let color_2 = Lab {
+            l: lab2[0],
+            a: lab2[1],
+            b: lab2[2],
+        };
+
+        assert_eq!(round(DECMC::new(color_1, color_2, lightness_weight, 1.0)), expected);
+    }
+
+    #[test]
+    fn acceptability_tests() {
+        assert_delta_e(0.0, 2.0, &[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]);
+        assert_delta_e(33.7401, 2.0, &[100.0, 0.005, -0.010], &[0.0, 0.0, 0.0]);
+        assert_delta_e(1.7387, 2.0, &[50.0000, 2.6772, -79.7751], &[50.0000, 0.0000, -82.7485]);
+        assert_delta_e(3.5048, 2.0, &[50.0000, 0.0000, 0.0000], &[50.0000, -1.0000, 2.0000]);
+        assert_delta_e(37.9233, 2.0, &[50.0000, 2.5000, 0.0000], &[73.0000, 25.0000, -18.0000]);
+        assert_delta_e(38.4758, 2.0, &[50.0000, 2.5000, 0.0000], &[61.0000, -5.0000, 29.0000]);
+        assert_delta_e(1.4205, 2.0, &[60.2574, -34.0099, 36.2677], &[60.4626, -34.1751, 39.4387]);
+        assert_delta_e(3.0604, 2.0, &[22.7233, 20.0904, -46.6940], &[23.0331, 14.9730, -42.5619]);
+        assert_delta_e(0.9528, 2.0, &[6.7747, -0.2908, -2.4247], &[5.8714, -0.0985, -2.2286]);
+        assert_delta_e(1.8891, 2.0, &[90.8027, -2.0831, 1.4410], &[91.1528, -1.6435, 0.0447]);
+    }
+
+    #[test]
+    fn perceptibility_tests() {
+        assert_delta_e(0.0, 1.0, &[0.0, 0.0, 0.0], &[0.0, 0.0, 0.0]);
+        assert_delta_e(67.4802, 1.0, &[100.0, 0.005, -0.010], &[0.0, 0.0, 0.0]);
+        assert_delta_e(42.1088, 1.0, &[50.0000, 2.5000, 0.0000], &[73.0000, 25.0000, -18.0000]);
+        assert_delta_e(39.4589, 1.0, &[50.0000, 2.5000, 0.0000], &[61.0000, -5.0000, 29.0000]);
+        assert_delta_e(1.4282, 1.0, &[60.2574, -34.0099, 36.2677], &[60.4626, -34.1751, 39.4387]);
+        assert_delta_e(1.8032, 1.0, &[6.7747, -0.2908, -2.4247], &[5.8714, -0.0985, -2.2286]);
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! DeltaE is a pure-Rust implementation of the [CIEDE2000
 //! algorithm](http://en.wikipedia.org/wiki/Color_difference#CIEDE2000) which
-//! serves to quantify the difference between two colors.
+//! serves to quantify the difference between two colors. The older CIE76,
+//! CIE94 and CMC l:c formulas are available as `DE1976`, `DE1994` and `DECMC`.
 //!
 //! ## Example:
 //!
@@ -36,6 +37,12 @@ extern crate lab;
 use std::f32;
 use lab::Lab;
 
+mod de1976;
+mod de1994;
 mod de2000;
+mod decmc;
 
+pub use de1976::DE1976;
+pub use de1994::DE1994;
 pub use de2000::DE2000;
+pub use decmc::DECMC;
@@ -0,0 +1,291 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::time;
+use std::time::SystemTime;
+
+use camino::Utf8Path;
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use fastnbt::{LongArray, Value};
+use flate2::Compression;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::ZlibEncoder;
+use itertools::Itertools;
+
+use crate::block_state_packing;
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+use crate::cli_arguments::CliArguments;
+
+/// First data version using the 1.18 chunk format with top-level `sections` and `block_states`.
+const MINIMUM_CHUNK_DATA_VERSION: i32 = 2860;
+
+const SECTOR_SIZE: usize = 4096;
+
+/// Blocks to place by chunk section Y, with their position within the section.
+type BlocksBySection<'a> = BTreeMap<i32, Vec<([usize; 3], &'a TextureWithBlockState)>>;
+
+/// Places the build directly into the region files of an existing world, with the origin being the position of the build's
+/// lowest north-western block. The world must not be opened in the game while this runs.
+pub fn place(
+    world_path: &Utf8Path,
+    cli_arguments: &CliArguments,
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<()> {
+    let region_directory = world_path.join(cli_arguments.world_dimension.region_directory());
+
+    let [origin_x, origin_y, origin_z] = cli_arguments.world_origin.0;
+
+    // Blocks to place by region, then chunk, then chunk section
+    let mut blocks_by_region: BTreeMap<(i32, i32), BTreeMap<(i32, i32), BlocksBySection>> = BTreeMap::new();
+
+    for position @ [x, y, z] in block_volume.positions() {
+        let world_position = [origin_x + x as i32, origin_y + y as i32, origin_z + z as i32];
+
+        let [chunk_x, section_y, chunk_z] = world_position.map(|coordinate| coordinate.div_euclid(16));
+
+        blocks_by_region.entry((chunk_x.div_euclid(32), chunk_z.div_euclid(32))).or_default()
+            .entry((chunk_x, chunk_z)).or_default()
+            .entry(section_y).or_default()
+            .push((world_position.map(|coordinate| coordinate.rem_euclid(16) as usize), &block_textures_and_states[block_volume.get(position)]));
+    }
+
+    for ((region_x, region_z), blocks_by_chunk) in blocks_by_region {
+        let region_path = region_directory.join(format!("r.{region_x}.{region_z}.mca"));
+
+        tracing::info!("Writing {} chunk(s) to '{}'...", blocks_by_chunk.len(), region_path);
+
+        let mut region = Region::read(&region_path)?;
+
+        for ((chunk_x, chunk_z), blocks_by_section) in blocks_by_chunk {
+            let chunk_index = chunk_x.rem_euclid(32) as usize + chunk_z.rem_euclid(32) as usize * 32;
+
+            let chunk_nbt = region.chunks[chunk_index].as_ref()
+                .ok_or(eyre!("Chunk {chunk_x}, {chunk_z} has not been generated yet. Load the area in the game once before placing the build."))?;
+
+            let mut chunk: Value = fastnbt::from_bytes(chunk_nbt)?;
+
+            place_in_chunk(&mut chunk, blocks_by_section, chunk_x, chunk_z)?;
+
+            region.chunks[chunk_index] = Some(fastnbt::to_bytes(&chunk)?);
+            region.timestamps[chunk_index] = SystemTime::now().duration_since(time::UNIX_EPOCH)?.as_secs() as u32;
+        }
+
+        fs::write(&region_path, region.to_bytes()?)?;
+    }
+
+    Ok(())
+}
+
+fn place_in_chunk(
+    chunk: &mut Value,
+    blocks_by_section: BlocksBySection,
+    chunk_x: i32,
+    chunk_z: i32
+) -> eyre::Result<()> {
+    let Value::Compound(chunk) = chunk else {
+        return Err(eyre!("Chunk {chunk_x}, {chunk_z} is not an NBT compound."));
+    };
+
+    let data_version = chunk.get("DataVersion").and_then(Value::as_i64).unwrap_or(0);
+
+    if data_version < MINIMUM_CHUNK_DATA_VERSION as i64 {
+        return Err(eyre!("Chunk {chunk_x}, {chunk_z} uses data version {data_version}, only worlds from Minecraft 1.18 or newer are supported."));
+    }
+
+    if !chunk.get("Status").and_then(Value::as_str).is_some_and(|status| status.ends_with("full")) {
+        return Err(eyre!("Chunk {chunk_x}, {chunk_z} is not fully generated yet. Load the area in the game once before placing the build."));
+    }
+
+    let Some(Value::List(sections)) = chunk.get_mut("sections") else {
+        return Err(eyre!("Chunk {chunk_x}, {chunk_z} has no sections."));
+    };
+
+    let mut replaced_positions = HashSet::new();
+
+    for (section_y, blocks) in blocks_by_section {
+        let section = sections.iter_mut()
+            .find_map(|section| match section {
+                Value::Compound(section) if section.get("Y").and_then(Value::as_i64) == Some(section_y as i64) => Some(section),
+                _ => None
+            })
+            .ok_or(eyre!("Chunk {chunk_x}, {chunk_z} has no section at Y {}, the build exceeds the world's height limits.", section_y * 16))?;
+
+        let Some(Value::Compound(block_states)) = section.get_mut("block_states") else {
+            return Err(eyre!("Section {section_y} of chunk {chunk_x}, {chunk_z} has no block states."));
+        };
+
+        let Some(Value::List(palette)) = block_states.get("palette") else {
+            return Err(eyre!("Section {section_y} of chunk {chunk_x}, {chunk_z} has no block state palette."));
+        };
+
+        let mut palette = palette.clone();
+
+        let mut palette_indices = match block_states.get("data") {
+            Some(Value::LongArray(longs)) => block_state_packing::unpack_aligned(longs, block_state_packing::bits_per_block(palette.len(), 4), 4096),
+            _ => vec![0; 4096]
+        };
+
+        for ([x, y, z], block) in blocks {
+            let palette_entry = to_palette_entry(block);
+
+            let palette_index = palette.iter().position(|existing_entry| existing_entry == &palette_entry).unwrap_or_else(|| {
+                palette.push(palette_entry);
+                palette.len() - 1
+            });
+
+            palette_indices[(y * 16 + z) * 16 + x] = palette_index;
+
+            replaced_positions.insert([chunk_x * 16 + x as i32, section_y * 16 + y as i32, chunk_z * 16 + z as i32]);
+        }
+
+        // Drop palette entries which are no longer used
+        let used_palette_indices = palette_indices.iter().copied().unique().sorted().collect::<Vec<_>>();
+        let new_palette_index_by_old = used_palette_indices.iter()
+            .enumerate()
+            .map(|(new_index, &old_index)| (old_index, new_index))
+            .collect::<HashMap<_, _>>();
+
+        let palette = used_palette_indices.iter().map(|&old_index| palette[old_index].clone()).collect::<Vec<_>>();
+        let palette_indices = palette_indices.iter().map(|old_index| new_palette_index_by_old[old_index]).collect::<Vec<_>>();
+
+        if palette.len() == 1 {
+            block_states.remove("data");
+        } else {
+            block_states.insert("data".into(), Value::LongArray(LongArray::new(block_state_packing::pack_aligned(
+                &palette_indices,
+                block_state_packing::bits_per_block(palette.len(), 4)
+            ))));
+        }
+
+        block_states.insert("palette".into(), Value::List(palette));
+
+        // Let the game recalculate lighting for the changed blocks
+        section.remove("BlockLight");
+        section.remove("SkyLight");
+    }
+
+    // Block entities of replaced blocks would otherwise remain without their block
+    if let Some(Value::List(block_entities)) = chunk.get_mut("block_entities") {
+        block_entities.retain(|block_entity| {
+            let Value::Compound(block_entity) = block_entity else {
+                return true;
+            };
+
+            let position = ["x", "y", "z"].map(|axis| block_entity.get(axis).and_then(Value::as_i64).unwrap_or(i64::MIN) as i32);
+
+            !replaced_positions.contains(&position)
+        });
+    }
+
+    // Heightmaps and lighting are recalculated by the game on load if they are missing or marked as outdated
+    chunk.remove("Heightmaps");
+    chunk.insert("isLightOn".into(), Value::Byte(0));
+
+    Ok(())
+}
+
+fn to_palette_entry(block: &TextureWithBlockState) -> Value {
+    let mut palette_entry = HashMap::from([("Name".to_string(), Value::String(block.block_id.clone()))]);
+
+    if let Some(block_state_properties) = &block.block_state_properties {
+        palette_entry.insert(
+            "Properties".into(),
+            Value::Compound(block_state_properties.iter().map(|(name, value)| (name.clone(), Value::String(value.clone()))).collect())
+        );
+    }
+
+    Value::Compound(palette_entry)
+}
+
+/// Decompressed chunk NBT and timestamps of the 32x32 chunks in a region file.
+struct Region {
+    chunks: Vec<Option<Vec<u8>>>,
+    timestamps: Vec<u32>,
+}
+
+impl Region {
+    fn read(path: &Utf8Path) -> eyre::Result<Self> {
+        let bytes = fs::read(path).map_err(|e| eyre!("Unable to read region file '{}', the build probably lies in an area that has not been generated yet: {e}", path))?;
+
+        if bytes.len() < 2 * SECTOR_SIZE {
+            return Err(eyre!("Region file '{}' is truncated.", path));
+        }
+
+        let mut chunks = vec![];
+        let mut timestamps = vec![];
+
+        for index in 0..1024 {
+            let location = u32::from_be_bytes(bytes[index * 4..index * 4 + 4].try_into()?);
+            let timestamp = u32::from_be_bytes(bytes[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4].try_into()?);
+
+            let sector_offset = (location >> 8) as usize * SECTOR_SIZE;
+
+            chunks.push(if location == 0 {
+                None
+            } else {
+                let corrupt_chunk_error = || eyre!("Chunk {index} in region file '{}' is corrupt or truncated.", path);
+
+                // The length counts the compression type byte in front of the data
+                let length = u32::from_be_bytes(bytes.get(sector_offset..sector_offset + 4).ok_or_else(corrupt_chunk_error)?.try_into()?) as usize;
+                let compression = *bytes.get(sector_offset + 4).ok_or_else(corrupt_chunk_error)?;
+                let compressed_data = length.checked_sub(1)
+                    .and_then(|data_length| bytes.get(sector_offset + 5..sector_offset + 5 + data_length))
+                    .ok_or_else(corrupt_chunk_error)?;
+
+                let mut data = vec![];
+
+                match compression {
+                    1 => { GzDecoder::new(compressed_data).read_to_end(&mut data)?; }
+                    2 => { ZlibDecoder::new(compressed_data).read_to_end(&mut data)?; }
+                    3 => data.extend(compressed_data),
+                    compression => return Err(eyre!("Chunk {index} in region file '{}' uses unsupported compression type {compression}.", path))
+                }
+
+                Some(data)
+            });
+            timestamps.push(timestamp);
+        }
+
+        Ok(Self { chunks, timestamps })
+    }
+
+    fn to_bytes(&self) -> eyre::Result<Vec<u8>> {
+        let mut header = vec![0u8; 2 * SECTOR_SIZE];
+        let mut chunk_sectors = vec![];
+
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let Some(chunk) = chunk else {
+                continue;
+            };
+
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(chunk)?;
+            let compressed_data = encoder.finish()?;
+
+            let mut sectors = Vec::with_capacity(compressed_data.len() + 5);
+            sectors.extend((compressed_data.len() as u32 + 1).to_be_bytes());
+            sectors.push(2);
+            sectors.extend(compressed_data);
+            sectors.resize(sectors.len().div_ceil(SECTOR_SIZE) * SECTOR_SIZE, 0);
+
+            let sector_offset = 2 + chunk_sectors.len() / SECTOR_SIZE;
+            let sector_count = sectors.len() / SECTOR_SIZE;
+
+            if sector_count > 255 {
+                return Err(eyre!("Chunk {index} is too large to be stored in a region file."));
+            }
+
+            header[index * 4..index * 4 + 4].copy_from_slice(&((sector_offset as u32) << 8 | sector_count as u32).to_be_bytes());
+            header[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4].copy_from_slice(&self.timestamps[index].to_be_bytes());
+
+            chunk_sectors.extend(sectors);
+        }
+
+        header.extend(chunk_sectors);
+
+        Ok(header)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks;
+use crate::blocks::TextureWithBlockState;
+
+/// Climate and color overrides of the overworld, nether and end biomes, see `biomes.txt`. Grass and foliage colors are
+/// looked up in the colormaps by temperature and downfall unless a biome overrides them.
+static BIOMES: Lazy<Vec<Biome>> = Lazy::new(|| {
+    include_str!("biomes.txt").lines()
+        .map(|line| {
+            let fields = line.split('|').collect::<Vec<_>>();
+
+            let (name, temperature, downfall, water_color, grass_color, foliage_color) = match fields[..] {
+                [name, temperature, downfall, water_color] => (name, temperature, downfall, water_color, None, None),
+                [name, temperature, downfall, water_color, grass_color] => (name, temperature, downfall, water_color, Some(grass_color), None),
+                [name, temperature, downfall, water_color, grass_color, foliage_color] => (name, temperature, downfall, water_color, Some(grass_color), Some(foliage_color)),
+                _ => panic!("Invalid biome line '{}'.", line)
+            };
+
+            Biome {
+                name,
+                temperature: temperature.parse().unwrap(),
+                downfall: downfall.parse().unwrap(),
+                water_color: parse_rgb(water_color),
+                grass_color: match grass_color {
+                    None => GrassColor::Colormap,
+                    Some("dark_forest") => GrassColor::DarkForest,
+                    Some(grass_color) => GrassColor::Fixed(parse_rgb(grass_color)),
+                },
+                foliage_color: foliage_color.map(parse_rgb),
+            }
+        })
+        .collect()
+});
+
+#[derive(Clone)]
+pub struct Biome {
+    pub name: &'static str,
+    pub temperature: f32,
+    pub downfall: f32,
+    pub water_color: [u8; 3],
+    pub grass_color: GrassColor,
+    pub foliage_color: Option<[u8; 3]>,
+}
+
+#[derive(Clone, Copy)]
+pub enum GrassColor {
+    Colormap,
+    /// The colormap color blended with a dark green.
+    DarkForest,
+    Fixed([u8; 3]),
+}
+
+impl FromStr for Biome {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BIOMES.iter()
+            .find(|biome| biome.name == s.strip_prefix("minecraft:").unwrap_or(s))
+            .cloned()
+            .ok_or("Invalid biome.")
+    }
+}
+
+/// Color in `r,g,b` notation.
+pub fn parse_rgb(rgb: &str) -> [u8; 3] {
+    match rgb.split(',').map(|channel| channel.parse::<u8>()).collect::<Result<Vec<_>, _>>() {
+        Ok(channels) if channels.len() == 3 => [channels[0], channels[1], channels[2]],
+        _ => panic!("Invalid color '{}'.", rgb)
+    }
+}
+
+/// Name of the biome the build's tinted blocks were colored for, `None` if it does not contain any.
+pub fn tint_biome_name<'a>(
+    biome: &'a Biome,
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> Option<&'a str> {
+    block_volume.texture_names()
+        .unique()
+        .any(|texture_name| blocks::is_tinted(&block_textures_and_states[texture_name].block_id))
+        .then_some(biome.name)
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+
+use camino::Utf8Path;
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use once_cell::sync::Lazy;
+
+use crate::cli_arguments::Dimension;
+use crate::helpers;
+
+/// Survival availability and rough acquisition cost of blocks, see `block_metadata.txt`. Costs go from 1 for blocks gathered
+/// in bulk, over 2 for simple crafting or smelting, 3 for farms, monuments and the like and 4 for rare materials up to 5
+/// for very rare or unobtainable blocks.
+static BLOCK_METADATA: Lazy<HashMap<&'static str, BlockMetadata>> = Lazy::new(|| {
+    include_str!("block_metadata.txt").lines()
+        .map(|line| {
+            let (block_id, cost, flags) = match line.split('|').collect::<Vec<_>>()[..] {
+                [block_id, cost] => (block_id, cost, vec![]),
+                [block_id, cost, flags] => (block_id, cost, flags.split(',').collect()),
+                _ => panic!("Invalid block metadata line '{}'.", line)
+            };
+
+            let mut block_metadata = BlockMetadata {
+                cost: cost.parse().unwrap(),
+                ..BlockMetadata::default()
+            };
+
+            for flag in flags {
+                match flag {
+                    "unobtainable" => block_metadata.obtainable = false,
+                    "non_renewable" => block_metadata.renewable = false,
+                    "silk_touch" => block_metadata.requires_silk_touch = true,
+                    "nether" => block_metadata.dimension = Dimension::Nether,
+                    "end" => block_metadata.dimension = Dimension::End,
+                    _ => panic!("Invalid block metadata flag '{}'.", flag)
+                }
+            }
+
+            (block_id, block_metadata)
+        })
+        .collect()
+});
+
+#[derive(Clone, Copy)]
+pub struct BlockMetadata {
+    pub obtainable: bool,
+    pub renewable: bool,
+    pub cost: u32,
+    pub requires_silk_touch: bool,
+    /// Dimension the block or its ingredients have to be gathered in.
+    pub dimension: Dimension,
+}
+
+/// Metadata of a renewable overworld block that is gathered in bulk, which blocks that are not listed are assumed to be.
+impl Default for BlockMetadata {
+    fn default() -> Self {
+        Self {
+            obtainable: true,
+            renewable: true,
+            cost: 1,
+            requires_silk_touch: false,
+            dimension: Dimension::Overworld,
+        }
+    }
+}
+
+pub fn block_metadata(block_id: &str) -> BlockMetadata {
+    BLOCK_METADATA.get(block_id).copied().unwrap_or_default()
+}
+
+/// Cost of each block in the palette, read from a user file with one `<block id>|<cost>` line per block where given and
+/// taken from the built-in metadata otherwise.
+pub fn block_costs<'a>(block_ids: impl Iterator<Item = &'a str>, block_costs_path: Option<&Utf8Path>) -> eyre::Result<HashMap<String, f32>> {
+    let mut block_costs = block_ids
+        .map(|block_id| (block_id.to_string(), block_metadata(block_id).cost as f32))
+        .collect::<HashMap<_, _>>();
+
+    if let Some(block_costs_path) = block_costs_path {
+        for (block_id, cost) in helpers::read_block_values::<f32>(block_costs_path, "COST")? {
+            if cost < 0.0 {
+                return Err(eyre!("The cost of '{block_id}' in '{block_costs_path}' cannot be negative."));
+            }
+
+            block_costs.insert(block_id, cost);
+        }
+    }
+
+    Ok(block_costs)
+}
\ No newline at end of file
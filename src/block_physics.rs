@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+use crate::blocks::TextureWithBlockState;
+use crate::cli_arguments::{CliArguments, Orientation, UnstableBlockMode};
+use crate::palette_presets::BlockPattern;
+
+/// Blocks that do not stay in place on their own, see `block_physics.txt`. Every other block is assumed to be stable.
+static BLOCK_PHYSICS: Lazy<HashMap<&'static str, BlockPhysics>> = Lazy::new(|| {
+    include_str!("block_physics.txt").lines()
+        .map(|line| match line.split('|').collect::<Vec<_>>()[..] {
+            [block_id, "falling"] => (block_id, BlockPhysics::Falling),
+            [block_id, "attached", support] => (block_id, BlockPhysics::Attached(match support {
+                "any" => Support::Any,
+                "sturdy" => Support::Sturdy,
+                "dirt" => Support::Dirt,
+                "bamboo" => Support::Bamboo,
+                "mushroom" => Support::Mushroom,
+                _ => panic!("Invalid block physics line '{}'.", line)
+            })),
+            [block_id, "needs_water"] => (block_id, BlockPhysics::NeedsWater),
+            [block_id, "needs_space"] => (block_id, BlockPhysics::NeedsSpace),
+            _ => panic!("Invalid block physics line '{}'.", line)
+        })
+        .collect()
+});
+
+/// Blocks that falling blocks drop through, as the game replaces them.
+const REPLACEABLE_BLOCK_IDS: [&str; 9] = [
+    "minecraft:short_grass",
+    "minecraft:grass",
+    "minecraft:fern",
+    "minecraft:tall_grass",
+    "minecraft:large_fern",
+    "minecraft:snow",
+    "minecraft:water",
+    "minecraft:seagrass",
+    "minecraft:tall_seagrass",
+];
+
+/// Blocks plants can grow on, the `#dirt` block tag and farmland.
+const DIRT_BLOCK_IDS: [&str; 11] = [
+    "minecraft:dirt",
+    "minecraft:grass_block",
+    "minecraft:coarse_dirt",
+    "minecraft:podzol",
+    "minecraft:rooted_dirt",
+    "minecraft:mycelium",
+    "minecraft:moss_block",
+    "minecraft:pale_moss_block",
+    "minecraft:mud",
+    "minecraft:muddy_mangrove_roots",
+    "minecraft:farmland",
+];
+
+/// Blocks bamboo can be planted on besides dirt, farmland excepted.
+const BAMBOO_SOIL_BLOCK_IDS: [&str; 6] = [
+    "minecraft:sand",
+    "minecraft:red_sand",
+    "minecraft:suspicious_sand",
+    "minecraft:gravel",
+    "minecraft:suspicious_gravel",
+    "minecraft:bamboo",
+];
+
+/// Blocks mushrooms grow on regardless of the light level.
+const MUSHROOM_SOIL_BLOCK_IDS: [&str; 4] = [
+    "minecraft:mycelium",
+    "minecraft:podzol",
+    "minecraft:crimson_nylium",
+    "minecraft:warped_nylium",
+];
+
+/// Blocks whose top face does not cover the whole block, so rails, torches and the like cannot be placed on them. Upper
+/// slabs, stairs and closed trapdoors are the exception, see [`has_sturdy_top`].
+static PARTIAL_TOP_BLOCK_PATTERNS: Lazy<Vec<BlockPattern>> = Lazy::new(|| [
+    "*_slab", "*_stairs", "*_trapdoor", "*_fence", "*_fence_gate", "*_wall", "*_pane", "*_bars", "*chain", "*_carpet", "snow",
+    "dirt_path", "farmland", "*_button", "*_sign", "*_banner", "*_bed", "*candle*", "*lantern", "*_head", "*_skull",
+    "*chest", "*anvil", "*campfire", "*cauldron", "composter", "lectern", "enchanting_table", "stonecutter", "grindstone",
+    "daylight_detector", "bell", "end_rod", "lightning_rod", "ladder", "cake", "flower_pot", "brewing_stand", "scaffolding",
+    "chorus_*", "*dripleaf", "pointed_dripstone", "*amethyst_bud", "amethyst_cluster", "sea_pickle", "turtle_egg",
+].into_iter().map(BlockPattern::new).collect());
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BlockPhysics {
+    /// Falls down unless there is a block below it, like sand or concrete powder.
+    Falling,
+    /// Breaks unless the block below it can hold it, like carpets, flowers or rails.
+    Attached(Support),
+    /// Dies or breaks outside of water, like coral or kelp.
+    NeedsWater,
+    /// Breaks next to other blocks, like cactus.
+    NeedsSpace,
+}
+
+/// What the block below an unstable block has to be for it to stay in place.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Support {
+    /// Any block, like below carpets and falling blocks.
+    Any,
+    /// A block with a full, solid top face, like below rails, torches and pressure plates.
+    Sturdy,
+    /// Dirt, grass and the like, below flowers, saplings and other plants.
+    Dirt,
+    /// Dirt, sand or gravel, below bamboo.
+    Bamboo,
+    /// Mycelium, podzol or nylium, below mushrooms.
+    Mushroom,
+}
+
+pub fn block_physics(block_id: &str) -> Option<BlockPhysics> {
+    BLOCK_PHYSICS.get(block_id).copied()
+}
+
+/// Blocks that fall down without a block below them, such as sand or anvils.
+pub fn falling_block_ids() -> impl Iterator<Item = &'static str> {
+    BLOCK_PHYSICS.iter()
+        .filter(|(_, block_physics)| matches!(block_physics, BlockPhysics::Falling))
+        .map(|(block_id, _)| *block_id)
+}
+
+/// How unstable blocks are handled for the chosen layout. Nothing is below the blocks of a ceiling, so they cannot be
+/// supported and are excluded instead.
+pub fn unstable_block_mode(cli_arguments: &CliArguments) -> UnstableBlockMode {
+    match (&cli_arguments.map_art, cli_arguments.orientation, cli_arguments.unstable_blocks) {
+        (None, Orientation::Ceiling, UnstableBlockMode::Supported) => UnstableBlockMode::Exclude,
+        (_, _, unstable_block_mode) => unstable_block_mode,
+    }
+}
+
+/// Whether supported falling and attached blocks have to be placed on top of a block that holds them. Floors and map art
+/// lie on the ground, which holds them anywhere, so only walls are planned.
+pub fn plans_support(cli_arguments: &CliArguments) -> bool {
+    cli_arguments.map_art.is_none()
+        && cli_arguments.orientation != Orientation::Floor
+        && unstable_block_mode(cli_arguments) == UnstableBlockMode::Supported
+}
+
+/// Whether the block may be part of the palette at all. Supported falling and attached blocks are placed only where the
+/// block below them can hold them.
+pub fn is_allowed(block_id: &str, unstable_block_mode: UnstableBlockMode) -> bool {
+    match (block_physics(block_id), unstable_block_mode) {
+        (None, _) | (_, UnstableBlockMode::Allow) => true,
+        (Some(BlockPhysics::NeedsWater | BlockPhysics::NeedsSpace), _) | (_, UnstableBlockMode::Exclude) => false,
+        (Some(_), UnstableBlockMode::Supported) => true,
+    }
+}
+
+/// What the block has to be placed on top of, `None` if it stays in place on its own.
+pub fn required_support(block_id: &str) -> Option<Support> {
+    match block_physics(block_id)? {
+        BlockPhysics::Falling => Some(Support::Any),
+        BlockPhysics::Attached(support) => Some(support),
+        BlockPhysics::NeedsWater | BlockPhysics::NeedsSpace => None,
+    }
+}
+
+/// Whether a block that needs the given support can be placed on top of this block.
+pub fn can_support(block: &TextureWithBlockState, support: Support) -> bool {
+    let block_id = block.block_id.as_str();
+
+    match support {
+        Support::Any => block_id != "minecraft:air" && !REPLACEABLE_BLOCK_IDS.contains(&block_id),
+        Support::Sturdy => has_sturdy_top(block),
+        Support::Dirt => DIRT_BLOCK_IDS.contains(&block_id),
+        Support::Bamboo => DIRT_BLOCK_IDS.contains(&block_id) && block_id != "minecraft:farmland" || BAMBOO_SOIL_BLOCK_IDS.contains(&block_id),
+        Support::Mushroom => MUSHROOM_SOIL_BLOCK_IDS.contains(&block_id),
+    }
+}
+
+/// Whether the top face of the block is full and solid.
+fn has_sturdy_top(block: &TextureWithBlockState) -> bool {
+    let block_id = block.block_id.as_str();
+    let property = |name: &str| block.block_state_properties.as_ref()
+        .and_then(|block_state_properties| block_state_properties.get(name))
+        .map(|value| value.as_str());
+
+    if block_id == "minecraft:air" || block_physics(block_id).is_some() {
+        return false;
+    }
+
+    // Upper slabs, stairs and closed trapdoors fill the top of the block
+    if matches!(property("type"), Some("top" | "double")) || property("half") == Some("top") && property("open") != Some("true") {
+        return true;
+    }
+
+    !PARTIAL_TOP_BLOCK_PATTERNS.iter().any(|block_pattern| block_pattern.matches(block_id))
+}
\ No newline at end of file
@@ -0,0 +1,51 @@
+/// Number of bits needed to store indices into a palette of the given size.
+pub fn bits_per_block(palette_size: usize, minimum_bits: usize) -> usize {
+    ((palette_size as f32).log2().ceil() as usize).max(minimum_bits)
+}
+
+/// Packs palette indices into longs with entries spanning across long boundaries, as used by Litematica.
+pub fn pack_spanning(palette_indices: &[usize], bits_per_block: usize) -> Vec<i64> {
+    let mut longs = vec![0i64; bits_per_block * palette_indices.len() / 64 + 1];
+
+    let mut bit_index = 0;
+
+    for &palette_index in palette_indices {
+        longs[bit_index / 64] |= (palette_index as i64) << (bit_index % 64);
+
+        if bit_index % 64 + bits_per_block >= 64 {
+            let written_bits = 64 - bit_index % 64;
+
+            bit_index += written_bits;
+            longs[bit_index / 64] |= (palette_index as i64) >> written_bits;
+            bit_index += bits_per_block - written_bits;
+        } else {
+            bit_index += bits_per_block;
+        }
+    }
+
+    longs
+}
+
+/// Packs palette indices into longs without entries spanning across long boundaries, as used by Anvil chunk sections since
+/// Minecraft 1.16.
+pub fn pack_aligned(palette_indices: &[usize], bits_per_block: usize) -> Vec<i64> {
+    let blocks_per_long = 64 / bits_per_block;
+
+    palette_indices.chunks(blocks_per_long)
+        .map(|long_palette_indices| {
+            long_palette_indices.iter()
+                .enumerate()
+                .fold(0u64, |long, (index, &palette_index)| long | (palette_index as u64) << (index * bits_per_block)) as i64
+        })
+        .collect()
+}
+
+/// Reverses [`pack_aligned`].
+pub fn unpack_aligned(longs: &[i64], bits_per_block: usize, block_count: usize) -> Vec<usize> {
+    let blocks_per_long = 64 / bits_per_block;
+    let mask = (1u64 << bits_per_block) - 1;
+
+    (0..block_count)
+        .map(|index| ((longs[index / blocks_per_long] as u64 >> (index % blocks_per_long * bits_per_block)) & mask) as usize)
+        .collect()
+}
\ No newline at end of file
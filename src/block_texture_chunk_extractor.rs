@@ -1,26 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use color_eyre::eyre;
+use color_eyre::eyre::eyre;
 use image::{GenericImageView, Rgba, RgbaImage};
 use image::imageops::FilterType;
 
-use crate::{blocks, CliArguments};
-use crate::blocks::TextureWithBlockState;
-use crate::cli_arguments::TextureFilteringMode;
+use crate::{block_physics, blocks, map_art, mcstructure_generator, CliArguments};
+use crate::blocks::{TextureSource, TextureWithBlockState};
+use crate::cli_arguments::{MapArtMode, TextureFilteringMode};
 
 pub struct BlockTextureData {
     pub block_textures_and_states: HashMap<String, TextureWithBlockState>,
     pub chunk_average_color_map: HashMap<String, Vec<Vec<Rgba<u8>>>>,
+    /// Width and height in pixels every block texture was scaled to.
+    pub texture_resolution: u32,
 }
 
 pub fn extract(cli_arguments: &CliArguments) -> eyre::Result<BlockTextureData> {
-    let texture_filtering_mode = if let Some(block_palette) = &cli_arguments.block_palette {
-        TextureFilteringMode::AllowList(block_palette.0.clone())
-    } else if cli_arguments.exclude_non_survival_blocks {
-        TextureFilteringMode::BlockList(include_str!("non_survival_blocks.txt").lines().map(|s| s.into()).collect::<Vec<_>>())
-    } else {
-        TextureFilteringMode::BlockList(vec![])
-    };
+    let texture_filtering_mode = TextureFilteringMode::from_cli_arguments(cli_arguments);
 
     let mut block_textures_and_states: HashMap<String, TextureWithBlockState> = HashMap::new();
     block_textures_and_states.extend([("air".into(), TextureWithBlockState {
@@ -28,23 +25,123 @@ pub fn extract(cli_arguments: &CliArguments) -> eyre::Result<BlockTextureData> {
         block_id: "minecraft:air".into(),
         block_state_properties: None,
     })]);
-    block_textures_and_states.extend(blocks::get_normal_block_textures(&texture_filtering_mode, &cli_arguments.block_textures_path)?);
-    block_textures_and_states.extend(blocks::get_stair_block_textures(&texture_filtering_mode, &cli_arguments.block_textures_path)?);
-    block_textures_and_states.extend(blocks::get_slab_block_textures(&texture_filtering_mode, &cli_arguments.block_textures_path)?);
-    block_textures_and_states.extend(blocks::get_rotate_4_way_textures(&texture_filtering_mode, &cli_arguments.block_textures_path)?);
-    // special cases: cauldron_side, fence, fence gate, campfire, daylight_detector
+
+    if let Some(map_art_mode) = &cli_arguments.map_art {
+        let shades: &[usize] = match map_art_mode {
+            MapArtMode::Flat => &[map_art::FLAT_SHADE],
+            MapArtMode::Staircased => &map_art::STAIRCASED_SHADES,
+        };
+
+        block_textures_and_states.extend(map_art::get_map_color_textures(&texture_filtering_mode, shades));
+    } else {
+        if cli_arguments.block_textures_paths.is_empty() {
+            return Err(eyre!("A block textures path is required unless generating map art."));
+        }
+
+        let texture_source = TextureSource::open(&cli_arguments.block_textures_paths, cli_arguments.animated_texture_mode)?;
+
+        // Plain texture folders have no block states and models to render blocks from
+        let model_block_textures = if texture_source.asset_names("blockstates/").is_empty() {
+            HashMap::new()
+        } else {
+            blocks::get_model_block_textures(&texture_filtering_mode, &texture_source, &cli_arguments.biome, cli_arguments.orientation)?
+        };
+
+        // Resource packs layered over a plain texture folder may only bring the block states of a few blocks, the block lists
+        // cover the rest
+        let model_block_ids = model_block_textures.values().map(|block| block.block_id.clone()).collect::<HashSet<_>>();
+
+        let mut listed_block_textures = HashMap::new();
+        listed_block_textures.extend(blocks::get_normal_block_textures(&texture_filtering_mode, &texture_source)?);
+        listed_block_textures.extend(blocks::get_rotate_4_way_textures(&texture_filtering_mode, &texture_source)?);
+        listed_block_textures.extend(blocks::get_stair_block_textures(&texture_filtering_mode, &texture_source)?);
+        listed_block_textures.extend(blocks::get_slab_block_textures(&texture_filtering_mode, &texture_source)?);
+        listed_block_textures.retain(|_, block| !model_block_ids.contains(&block.block_id));
+
+        let texture_count = listed_block_textures.len();
+
+        // Rendered block states already show the side facing the viewer, listed textures are those of a wall facing south
+        let listed_block_textures = listed_block_textures.into_iter()
+            .filter_map(|(name, block)| Some((name, cli_arguments.orientation.reorient_block(block, &texture_source)?)))
+            .collect::<HashMap<_, _>>();
+
+        if listed_block_textures.len() < texture_count {
+            tracing::info!("Excluded {} texture(s) of blocks that cannot be turned into the chosen orientation or whose visible face is unknown.", texture_count - listed_block_textures.len());
+        }
+
+        block_textures_and_states.extend(listed_block_textures);
+        block_textures_and_states.extend(model_block_textures);
+    }
+
+    let texture_count = block_textures_and_states.len();
+
+    block_textures_and_states = block_textures_and_states.into_iter()
+        .filter_map(|(name, block)| Some((name, TextureWithBlockState {
+            block_id: cli_arguments.minecraft_version.block_id(&block.block_id)?,
+            ..block
+        })))
+        .collect();
+
+    if block_textures_and_states.len() < texture_count {
+        tracing::info!("Excluded {} texture(s) of blocks that do not exist in Minecraft {}.", texture_count - block_textures_and_states.len(), cli_arguments.minecraft_version.name);
+    }
+
+    // Bedrock Edition lacks some blocks of Java Edition, which fail the .mcstructure output unless they are left out
+    if cli_arguments.exclude_non_bedrock_blocks && cli_arguments.output_path.as_ref().is_some_and(|output_path| output_path.extension() == Some("mcstructure")) {
+        let non_bedrock_block_ids = block_textures_and_states.values()
+            .map(|block| block.block_id.clone())
+            .filter(|block_id| !mcstructure_generator::exists_on_bedrock(block_id))
+            .collect::<BTreeSet<_>>();
+
+        block_textures_and_states.retain(|_, block| !non_bedrock_block_ids.contains(&block.block_id));
+
+        if !non_bedrock_block_ids.is_empty() {
+            tracing::warn!("Excluded {} block(s) that do not exist on Bedrock Edition: {}", non_bedrock_block_ids.len(), non_bedrock_block_ids.into_iter().collect::<Vec<_>>().join(", "));
+        }
+    }
+
+    // Unstable blocks that no block of the palette can hold could never be placed
+    if block_physics::plans_support(cli_arguments) {
+        let unsupported_texture_names = block_textures_and_states.iter()
+            .filter(|(_, block)| block_physics::required_support(&block.block_id)
+                .is_some_and(|support| !block_textures_and_states.values().any(|block_below| block_physics::can_support(block_below, support))))
+            .map(|(texture_name, _)| texture_name.clone())
+            .collect::<Vec<_>>();
+
+        for texture_name in &unsupported_texture_names {
+            block_textures_and_states.remove(texture_name);
+        }
+
+        if !unsupported_texture_names.is_empty() {
+            tracing::info!("Excluded {} texture(s) of unstable blocks that no block of the palette can hold.", unsupported_texture_names.len());
+        }
+    }
+
+    // Resource packs may mix resolutions, so every texture is scaled up to the highest one without blurring its pixels
+    let texture_resolution = block_textures_and_states.values().map(|block| block.texture.width()).max().unwrap_or(16);
+
+    for block in block_textures_and_states.values_mut().filter(|block| block.texture.width() != texture_resolution) {
+        block.texture = block.texture.resize_exact(texture_resolution, texture_resolution, FilterType::Nearest);
+    }
+
+    if texture_resolution != 16 {
+        tracing::info!("Using block textures at {texture_resolution}x{texture_resolution} pixels.");
+    }
+
+    let chunk_size = texture_resolution as usize / cli_arguments.chunk_resolution;
 
     let block_chunk_data = block_textures_and_states.iter()
         .map(|(name, TextureWithBlockState { texture, .. })| {
             let mut chunks_average_color = vec![vec![Rgba([0; 4]); cli_arguments.chunk_resolution]; cli_arguments.chunk_resolution];
 
+            #[allow(clippy::needless_range_loop)]
             for x in 0..cli_arguments.chunk_resolution {
                 for y in 0..cli_arguments.chunk_resolution {
                     let chunk = texture.crop_imm(
-                        (x * 16 / cli_arguments.chunk_resolution) as u32,
-                        (y * 16 / cli_arguments.chunk_resolution) as u32,
-                        (16 / cli_arguments.chunk_resolution) as u32,
-                        (16 / cli_arguments.chunk_resolution) as u32
+                        (x * chunk_size) as u32,
+                        (y * chunk_size) as u32,
+                        chunk_size as u32,
+                        chunk_size as u32
                     );
 
                     let image_buffer = chunk.resize(1, 1, FilterType::Triangle);
@@ -57,8 +154,17 @@ pub fn extract(cli_arguments: &CliArguments) -> eyre::Result<BlockTextureData> {
         })
         .collect();
 
+    // Only placed below carpets after matching, so it is not part of the chunk data
+    if cli_arguments.map_art.is_some() {
+        let mut support_block = map_art::support_block();
+        support_block.texture = support_block.texture.resize_exact(texture_resolution, texture_resolution, FilterType::Nearest);
+
+        block_textures_and_states.insert(map_art::SUPPORT_TEXTURE_NAME.into(), support_block);
+    }
+
     Ok(BlockTextureData {
         block_textures_and_states,
-        chunk_average_color_map: block_chunk_data
+        chunk_average_color_map: block_chunk_data,
+        texture_resolution,
     })
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+/// Texture names of the blocks of a build by position, with Y pointing upwards. Positions without a block hold `air`.
+pub struct BlockVolume {
+    pub size: [usize; 3],
+    blocks: Vec<String>,
+}
+
+impl BlockVolume {
+    pub fn new(size: [usize; 3]) -> Self {
+        Self {
+            size,
+            blocks: vec!["air".into(); size.iter().product()],
+        }
+    }
+
+    /// Lays the image out as a wall in the X/Y plane, one block thick.
+    pub fn wall(output_blocks: &[Vec<String>]) -> Self {
+        let block_height = output_blocks.first().map(|column| column.len()).unwrap_or(0);
+
+        let mut block_volume = Self::new([output_blocks.len(), block_height, 1]);
+
+        for (x, column) in output_blocks.iter().enumerate() {
+            for (y, texture_name) in column.iter().enumerate() {
+                // The image's Y axis points downwards
+                block_volume.set([x, block_height - 1 - y, 0], texture_name.clone());
+            }
+        }
+
+        block_volume
+    }
+
+    pub fn get(&self, position: [usize; 3]) -> &String {
+        &self.blocks[self.index(position)]
+    }
+
+    pub fn set(&mut self, position: [usize; 3], texture_name: String) {
+        let index = self.index(position);
+        self.blocks[index] = texture_name;
+    }
+
+    /// All positions ordered by Y, then Z, then X, which is the order most schematic formats store their blocks in.
+    pub fn positions(&self) -> impl Iterator<Item = [usize; 3]> {
+        let [size_x, size_y, size_z] = self.size;
+
+        (0..size_y).flat_map(move |y| (0..size_z).flat_map(move |z| (0..size_x).map(move |x| [x, y, z])))
+    }
+
+    /// Texture names of all blocks in the same order as [`BlockVolume::positions`].
+    pub fn texture_names(&self) -> impl Iterator<Item = &String> {
+        self.blocks.iter()
+    }
+
+    fn index(&self, [x, y, z]: [usize; 3]) -> usize {
+        (y * self.size[2] + z) * self.size[0] + x
+    }
+}
\ No newline at end of file
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+
+/// Textures of the faces of blocks which do not look the same from every side, see `block_faces.txt`. Faces are named
+/// relative to the block's `facing` or `axis` property if it has one, otherwise relative to the world.
+static BLOCK_FACES: Lazy<HashMap<&'static str, HashMap<&'static str, &'static str>>> = Lazy::new(|| {
+    include_str!("block_faces.txt").lines()
+        .map(|line| match line.split('|').collect::<Vec<_>>()[..] {
+            [block_id, faces] => (
+                block_id,
+                faces.split(',')
+                    .map(|face_definition| match face_definition.split('=').collect::<Vec<_>>()[..] {
+                        [face, texture_name] => (face, texture_name),
+                        _ => panic!("Invalid face definition '{}'.", face_definition)
+                    })
+                    .collect()
+            ),
+            _ => panic!("Invalid block faces line '{}'.", line)
+        })
+        .collect()
+});
+
+pub enum FaceTexture {
+    /// The block looks the same from every side.
+    Uniform,
+    Known(&'static str),
+    Unknown,
+}
+
+/// Texture of a face (`front`, `back`, `top`, `bottom` or `side`) of a block. Backs fall back to the sides and bottoms to the
+/// tops, as most blocks do not have dedicated textures for them.
+pub fn face_texture(block_id: &str, face: &str) -> FaceTexture {
+    let Some(faces) = BLOCK_FACES.get(block_id) else {
+        return FaceTexture::Uniform;
+    };
+
+    let fallback_face = match face {
+        "back" => Some("side"),
+        "bottom" => Some("top"),
+        _ => None,
+    };
+
+    faces.get(face)
+        .or_else(|| fallback_face.and_then(|fallback_face| faces.get(fallback_face)))
+        .map(|&texture_name| FaceTexture::Known(texture_name))
+        .unwrap_or(FaceTexture::Unknown)
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use image::{GenericImageView, Rgba};
+use once_cell::sync::Lazy;
+
+use crate::biomes;
+use crate::biomes::{Biome, GrassColor};
+use crate::blocks::TextureSource;
+
+/// How the tinted faces of each block are colored, see `block_tints.txt`. Blocks tinted in ways that do not depend on the
+/// biome, like redstone wire, are not listed and stay excluded.
+static BLOCK_TINTS: Lazy<HashMap<&'static str, Tint>> = Lazy::new(|| {
+    include_str!("block_tints.txt").lines()
+        .map(|line| match line.split('|').collect::<Vec<_>>()[..] {
+            [block_id, "grass"] => (block_id, Tint::Grass),
+            [block_id, "foliage"] => (block_id, Tint::Foliage),
+            [block_id, "water"] => (block_id, Tint::Water),
+            [block_id, rgb] => (block_id, Tint::Fixed(biomes::parse_rgb(rgb))),
+            _ => panic!("Invalid block tint line '{}'.", line)
+        })
+        .collect()
+});
+
+/// Color blended into the grass of dark forests.
+const DARK_FOREST_GRASS_COLOR: [u8; 3] = [0x28, 0x34, 0x0A];
+
+#[derive(Clone, Copy)]
+enum Tint {
+    Grass,
+    Foliage,
+    Water,
+    Fixed([u8; 3]),
+}
+
+/// Tint colors of a biome, with grass and foliage colors read from the texture source's colormaps.
+pub struct BiomeTints {
+    grass_color: Option<[u8; 3]>,
+    foliage_color: Option<[u8; 3]>,
+    water_color: [u8; 3],
+}
+
+impl BiomeTints {
+    pub fn new(biome: &Biome, texture_source: &TextureSource) -> Self {
+        let grass_colormap_color = || colormap_color(texture_source, "grass", biome);
+
+        Self {
+            grass_color: match biome.grass_color {
+                GrassColor::Colormap => grass_colormap_color(),
+                GrassColor::DarkForest => grass_colormap_color().map(|color| {
+                    [0, 1, 2].map(|index| (((color[index] & 0xFE) as u32 + DARK_FOREST_GRASS_COLOR[index] as u32) / 2) as u8)
+                }),
+                GrassColor::Fixed(color) => Some(color),
+            },
+            foliage_color: biome.foliage_color.or_else(|| colormap_color(texture_source, "foliage", biome)),
+            water_color: biome.water_color,
+        }
+    }
+
+    /// Color the tinted faces of a block are multiplied with, `None` if the block is not tinted or its colormap is missing.
+    pub fn tint_color(&self, block_id: &str) -> Option<[u8; 3]> {
+        match BLOCK_TINTS.get(block_id)? {
+            Tint::Grass => self.grass_color,
+            Tint::Foliage => self.foliage_color,
+            Tint::Water => Some(self.water_color),
+            Tint::Fixed(color) => Some(*color),
+        }
+    }
+}
+
+pub fn is_tinted(block_id: &str) -> bool {
+    BLOCK_TINTS.contains_key(block_id)
+}
+
+/// Looks up the color of a biome in a 256x256 colormap, which is indexed by temperature and downfall.
+fn colormap_color(texture_source: &TextureSource, colormap_name: &str, biome: &Biome) -> Option<[u8; 3]> {
+    let asset_path = format!("textures/colormap/{colormap_name}.png");
+
+    let colormap = match texture_source.read_asset(&asset_path).map(|bytes| image::load_from_memory(&bytes)) {
+        Some(Ok(colormap)) => colormap,
+        Some(Err(e)) => {
+            tracing::warn!("Unable to read colormap '{}': {e}", asset_path);
+            return None;
+        }
+        None => {
+            tracing::warn!("Unable to find colormap '{}', blocks tinted with it are excluded.", asset_path);
+            return None;
+        }
+    };
+
+    let temperature = biome.temperature.clamp(0.0, 1.0);
+    let downfall = biome.downfall.clamp(0.0, 1.0) * temperature;
+
+    let x = ((1.0 - temperature) * (colormap.width() - 1) as f32) as u32;
+    let y = ((1.0 - downfall) * (colormap.height() - 1) as f32) as u32;
+
+    let Rgba([red, green, blue, _]) = colormap.get_pixel(x, y);
+
+    Some([red, green, blue])
+}
\ No newline at end of file
@@ -1,10 +1,13 @@
 use std::collections::HashMap;
 
-use camino::Utf8Path;
-use color_eyre::eyre::eyre;
 use image::DynamicImage;
+use itertools::Itertools;
 
+pub use block_faces::{face_texture, FaceTexture};
+pub use block_tints::{is_tinted, BiomeTints};
+pub use model_blocks::get_model_block_textures;
 pub use normal_blocks::get_normal_block_textures;
+pub use texture_source::TextureSource;
 
 use crate::cli_arguments::TextureFilteringMode;
 
@@ -12,73 +15,43 @@ pub use rotate_4_way_blocks::get_rotate_4_way_textures;
 pub use slab_blocks::get_slab_block_textures;
 pub use stair_blocks::get_stair_block_textures;
 
+mod block_faces;
+mod block_tints;
+mod model_blocks;
 mod normal_blocks;
 mod rotate_4_way_blocks;
 mod stair_blocks;
 mod slab_blocks;
+mod texture_source;
 
-pub fn get_block_textures(texture_filtering_mode: &TextureFilteringMode, block_textures_path: &Utf8Path, filter: &[&str]) -> Vec<(String, TextureWithBlockState)> {
+pub fn get_block_textures(texture_filtering_mode: &TextureFilteringMode, texture_source: &TextureSource, filter: &[&str]) -> Vec<(String, TextureWithBlockState)> {
     filter.iter()
         .filter_map(|texture_info| {
-            let (texture_name, block_id, block_state_properties) = match &texture_info.split("|").collect::<Vec<_>>()[..] {
-                &[texture_name, block_id] => (texture_name, block_id, None),
-                &[texture_name, block_id, block_state_properties] => (
+            let (texture_name, block_id, block_state_properties) = match texture_info.split('|').collect::<Vec<_>>()[..] {
+                [texture_name, block_id] => (texture_name, block_id, None),
+                [texture_name, block_id, block_state_properties] => (
                     texture_name,
                     block_id,
-                    Some(block_state_properties.split(",")
+                    Some(block_state_properties.split(',')
                         .map(|property_definition| {
-                            match &property_definition.split("=").collect::<Vec<_>>()[..] {
-                                &[name, value] => (name.to_string(), value.to_string()),
-                                _ => Err(eyre!("Invalid property definition '{}'.", property_definition)).unwrap()
+                            match property_definition.split('=').collect::<Vec<_>>()[..] {
+                                [name, value] => (name.to_string(), value.to_string()),
+                                _ => panic!("Invalid property definition '{}'.", property_definition)
                             }
                         })
                         .collect::<HashMap<_, _>>()
                     )
                 ),
-                _ => Err(eyre!("Invalid texture info line '{}'.", texture_info)).unwrap()
+                _ => panic!("Invalid texture info line '{}'.", texture_info)
             };
 
-            let texture_path = block_textures_path.join(format!("{texture_name}.png"));
-
-            let result = match image::open(&texture_path) {
-                Ok(texture) => {
-                    let texture = texture.crop_imm(0, 0, 16, 16);
-                    Some((texture_name.to_string(), texture))
-                },
-                Err(e) => {
-                    tracing::warn!("Unable to find texture '{}': {e}", texture_path);
-                    None
-                }
-            };
-
-            match texture_filtering_mode {
-                TextureFilteringMode::AllowList(allowed_textures) => {
-                    result.and_then(|(name, texture)| {
-                        if allowed_textures.contains(&block_id.into()) {
-                            Some((name, TextureWithBlockState {
-                                texture,
-                                block_id: block_id.into(),
-                                block_state_properties,
-                            }))
-                        } else {
-                            None
-                        }
-                    })
-                }
-                TextureFilteringMode::BlockList(blocked_textures) => {
-                    result.and_then(|(name, texture)| {
-                        if blocked_textures.contains(&block_id.into()) {
-                            None
-                        } else {
-                            Some((name, TextureWithBlockState {
-                                texture,
-                                block_id: block_id.into(),
-                                block_state_properties,
-                            }))
-                        }
-                    })
-                }
-            }
+            texture_source.load_texture(texture_name)
+                .filter(|_| texture_filtering_mode.allows(block_id))
+                .map(|texture| (texture_name.to_string(), TextureWithBlockState {
+                    texture,
+                    block_id: block_id.into(),
+                    block_state_properties,
+                }))
         })
         .collect::<Vec<_>>()
 }
@@ -87,4 +60,21 @@ pub struct TextureWithBlockState {
     pub texture: DynamicImage,
     pub block_id: String,
     pub block_state_properties: Option<HashMap<String, String>>
+}
+
+impl TextureWithBlockState {
+    /// Block state in command/Sponge notation, e.g. `minecraft:oak_stairs[facing=east,half=bottom]`.
+    pub fn block_state_string(&self) -> String {
+        match &self.block_state_properties {
+            Some(block_state_properties) if !block_state_properties.is_empty() => format!(
+                "{}[{}]",
+                self.block_id,
+                block_state_properties.iter()
+                    .sorted()
+                    .map(|(name, value)| format!("{name}={value}"))
+                    .join(",")
+            ),
+            _ => self.block_id.clone()
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,508 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ops::Neg;
+
+use color_eyre::eyre;
+use color_eyre::eyre::WrapErr;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use itertools::Itertools;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::biomes::Biome;
+use crate::blocks::{BiomeTints, TextureSource, TextureWithBlockState};
+use crate::cli_arguments::{Orientation, TextureFilteringMode};
+
+/// Model faces with the direction they face.
+const FACES: [(&str, [i32; 3]); 6] = [
+    ("north", [0, 0, -1]),
+    ("south", [0, 0, 1]),
+    ("east", [1, 0, 0]),
+    ("west", [-1, 0, 0]),
+    ("up", [0, 1, 0]),
+    ("down", [0, -1, 0]),
+];
+
+/// Maximum length of model parent chains and texture variable references, guarding against cycles in resource packs.
+const MAX_REFERENCE_DEPTH: usize = 32;
+
+/// Maximum number of property combinations enumerated for a multipart block, which keeps blocks whose parts depend on
+/// many properties, such as redstone wire, from flooding the palette.
+const MAX_MULTIPART_STATES: usize = 256;
+
+/// Values of connection properties that are not `none`, e.g. the sides of walls and redstone wire.
+const CONNECTION_VALUES: [&str; 4] = ["low", "tall", "side", "up"];
+
+#[derive(Deserialize)]
+struct BlockStateDefinition {
+    variants: Option<HashMap<String, Variants>>,
+    multipart: Option<Vec<MultipartCase>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Variants {
+    Single(Variant),
+    /// Models picked at random when placed, of which only the first is used.
+    Weighted(Vec<Variant>),
+}
+
+impl Variants {
+    fn first(&self) -> Option<&Variant> {
+        match self {
+            Variants::Single(variant) => Some(variant),
+            Variants::Weighted(variants) => variants.first(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Variant {
+    model: String,
+    #[serde(default)]
+    x: i32,
+    #[serde(default)]
+    y: i32,
+}
+
+/// Part of a multipart block state, e.g. one side of a fence, applied if the block state matches its condition.
+#[derive(Deserialize)]
+struct MultipartCase {
+    when: Option<Condition>,
+    apply: Variants,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Condition {
+    Or {
+        #[serde(rename = "OR")]
+        conditions: Vec<Condition>,
+    },
+    And {
+        #[serde(rename = "AND")]
+        conditions: Vec<Condition>,
+    },
+    /// Property values that all have to match, each allowing multiple values separated by `|`.
+    Properties(HashMap<String, String>),
+}
+
+impl Condition {
+    fn matches(&self, block_state_properties: &HashMap<String, String>) -> bool {
+        match self {
+            Condition::Or { conditions } => conditions.iter().any(|condition| condition.matches(block_state_properties)),
+            Condition::And { conditions } => conditions.iter().all(|condition| condition.matches(block_state_properties)),
+            Condition::Properties(properties) => properties.iter().all(|(name, values)| {
+                block_state_properties.get(name).is_some_and(|value| values.split('|').any(|allowed_value| allowed_value == value))
+            }),
+        }
+    }
+
+    fn collect_property_values(&self, property_values: &mut BTreeMap<String, BTreeSet<String>>) {
+        match self {
+            Condition::Or { conditions } | Condition::And { conditions } => {
+                for condition in conditions {
+                    condition.collect_property_values(property_values);
+                }
+            }
+            Condition::Properties(properties) => {
+                for (name, values) in properties {
+                    property_values.entry(name.clone()).or_default().extend(values.split('|').map(|value| value.to_string()));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Model {
+    parent: Option<String>,
+    #[serde(default)]
+    textures: HashMap<String, String>,
+    elements: Option<Vec<Element>>,
+}
+
+#[derive(Deserialize)]
+struct Element {
+    from: [f32; 3],
+    to: [f32; 3],
+    rotation: Option<ElementRotation>,
+    faces: HashMap<String, Face>,
+}
+
+#[derive(Deserialize)]
+struct ElementRotation {
+    angle: f32,
+}
+
+#[derive(Deserialize)]
+struct Face {
+    texture: String,
+    uv: Option<[f32; 4]>,
+    #[serde(default)]
+    rotation: u32,
+    tintindex: Option<i32>,
+}
+
+/// Properties of a block state with the model variants making up its appearance.
+type BlockStateParts<'a> = (Option<HashMap<String, String>>, Vec<&'a Variant>);
+
+/// A model with the texture variables and elements inherited from its parents.
+struct ResolvedModel<'a> {
+    textures: HashMap<&'a str, &'a str>,
+    elements: &'a [Element],
+}
+
+/// An element face facing the viewer, with its bounds on screen and its distance towards the viewer.
+struct VisibleFace<'a> {
+    face_name: &'static str,
+    face: &'a Face,
+    element: &'a Element,
+    variant: &'a Variant,
+    texture_name: &'a str,
+    screen_bounds: [f32; 4],
+    depth: f32,
+}
+
+/// Renders what the viewer sees of every block state defined by the block states and models of the texture source, so new
+/// blocks, partial blocks like fences or carpets and resource pack models are picked up without maintaining block lists.
+/// Blocks are viewed from the side the orientation faces, e.g. from above for floors, so the block states need no turning
+/// afterwards. Tinted faces like those of grass and leaves are colored for the given biome.
+pub fn get_model_block_textures(texture_filtering_mode: &TextureFilteringMode, texture_source: &TextureSource, biome: &Biome, orientation: Orientation) -> eyre::Result<HashMap<String, TextureWithBlockState>> {
+    let biome_tints = BiomeTints::new(biome, texture_source);
+
+    let mut models = HashMap::new();
+    let mut textures = HashMap::new();
+    let mut block_textures_and_states = HashMap::new();
+
+    for file_name in texture_source.asset_names("blockstates/") {
+        let Some(block_name) = file_name.strip_suffix(".json") else {
+            continue;
+        };
+
+        let block_id = format!("minecraft:{block_name}");
+
+        if !texture_filtering_mode.allows(&block_id) {
+            continue;
+        }
+
+        let Some(block_state_definition) = read_json::<BlockStateDefinition>(texture_source, &format!("blockstates/{file_name}"))? else {
+            continue;
+        };
+
+        let mut block_states = block_state_definition.variants.iter()
+            .flatten()
+            .filter_map(|(variant_name, variants)| Some((parse_variant_name(variant_name), vec![variants.first()?])))
+            .collect::<Vec<_>>();
+
+        if let Some(multipart_cases) = &block_state_definition.multipart {
+            block_states.extend(multipart_block_states(&block_id, multipart_cases));
+        }
+
+        for (block_state_properties, variants) in block_states {
+            let model_chains = variants.iter()
+                .map(|variant| load_model_chain(texture_source, &mut models, &variant.model))
+                .collect::<eyre::Result<Option<Vec<_>>>>()?;
+
+            let Some(texture) = model_chains.and_then(|model_chains| {
+                render_block_state(texture_source, &models, &mut textures, biome_tints.tint_color(&block_id), orientation, variants.into_iter().zip(model_chains))
+            }) else {
+                continue;
+            };
+
+            let block = TextureWithBlockState {
+                texture,
+                block_id: block_id.clone(),
+                block_state_properties,
+            };
+
+            block_textures_and_states.insert(block.block_state_string(), block);
+        }
+    }
+
+    tracing::info!("Derived {} block state(s) from block models.", block_textures_and_states.len());
+
+    Ok(block_textures_and_states)
+}
+
+/// Reads and parses a JSON asset, `None` if it does not exist.
+fn read_json<T: DeserializeOwned>(texture_source: &TextureSource, asset_path: &str) -> eyre::Result<Option<T>> {
+    texture_source.read_asset(asset_path)
+        .map(|bytes| serde_json::from_slice(&bytes).wrap_err_with(|| format!("Invalid block asset '{asset_path}'.")))
+        .transpose()
+}
+
+/// Parses a variant name such as `axis=y,waterlogged=false` into block state properties. The empty name used by blocks
+/// without properties has none.
+fn parse_variant_name(variant_name: &str) -> Option<HashMap<String, String>> {
+    if variant_name.is_empty() {
+        return None;
+    }
+
+    variant_name.split(',')
+        .map(|property_definition| property_definition.split_once('=').map(|(name, value)| (name.to_string(), value.to_string())))
+        .collect()
+}
+
+/// Enumerates every combination of the property values a multipart block's conditions check for, together with the parts
+/// applied for it.
+fn multipart_block_states<'a>(block_id: &str, multipart_cases: &'a [MultipartCase]) -> Vec<BlockStateParts<'a>> {
+    let mut property_values = BTreeMap::new();
+
+    for condition in multipart_cases.iter().filter_map(|multipart_case| multipart_case.when.as_ref()) {
+        condition.collect_property_values(&mut property_values);
+    }
+
+    // Conditions usually only check for the values that add a part, e.g. a connected fence side
+    for values in property_values.values_mut() {
+        if values.iter().all(|value| value == "true" || value == "false") {
+            values.insert("false".into());
+        } else if values.iter().all(|value| CONNECTION_VALUES.contains(&value.as_str())) {
+            values.insert("none".into());
+        }
+    }
+
+    let state_count = property_values.values().map(|values| values.len()).product::<usize>();
+
+    if state_count > MAX_MULTIPART_STATES {
+        tracing::info!("Skipping block '{}', which has {} multipart block states.", block_id, state_count);
+        return vec![];
+    }
+
+    property_values.iter()
+        .map(|(name, values)| values.iter().map(move |value| (name.clone(), value.clone())))
+        .multi_cartesian_product()
+        .map(|properties| properties.into_iter().collect::<HashMap<_, _>>())
+        // Blocks without any conditions still have their single, unconditional state
+        .chain(property_values.is_empty().then(HashMap::new))
+        .map(|block_state_properties| {
+            let variants = multipart_cases.iter()
+                .filter(|multipart_case| multipart_case.when.as_ref().is_none_or(|condition| condition.matches(&block_state_properties)))
+                .filter_map(|multipart_case| multipart_case.apply.first())
+                .collect();
+
+            (Some(block_state_properties).filter(|block_state_properties| !block_state_properties.is_empty()), variants)
+        })
+        .collect()
+}
+
+/// Renders the faces of a block state's models that face the viewer into a tile as large as the largest texture used, nearer
+/// faces covering farther ones and tinted faces multiplied with the tint color. `None` if nothing is visible, a model or
+/// texture is missing or a visible face is tinted without a tint color.
+fn render_block_state<'a>(
+    texture_source: &TextureSource,
+    models: &'a HashMap<String, Option<Model>>,
+    textures: &mut HashMap<String, Option<DynamicImage>>,
+    tint_color: Option<[u8; 3]>,
+    orientation: Orientation,
+    variants: impl Iterator<Item = (&'a Variant, Vec<String>)>
+) -> Option<DynamicImage> {
+    let mut visible_faces = vec![];
+
+    for (variant, model_chain) in variants {
+        let model = resolve_model(models, &model_chain)?;
+
+        // Rotated elements, such as the crossed planes of plants, are not rendered
+        for element in model.elements.iter().filter(|element| element.rotation.as_ref().is_none_or(|rotation| rotation.angle == 0.0)) {
+            for (face_name, direction) in FACES {
+                if rotate_y(rotate_x(direction, variant.x), variant.y) != orientation.view_direction() {
+                    continue;
+                }
+
+                let Some(face) = element.faces.get(face_name) else {
+                    continue;
+                };
+
+                if face.tintindex.is_some() && tint_color.is_none() {
+                    return None;
+                }
+
+                let [from, to] = [element.from, element.to].map(|corner| to_screen(corner, variant, orientation));
+
+                visible_faces.push(VisibleFace {
+                    face_name,
+                    face,
+                    element,
+                    variant,
+                    texture_name: resolve_texture_name(&model.textures, &face.texture)?,
+                    screen_bounds: [from[0].min(to[0]), from[1].min(to[1]), from[0].max(to[0]), from[1].max(to[1])],
+                    depth: from[2].max(to[2]),
+                });
+            }
+        }
+    }
+
+    // Stable, so later elements cover earlier ones at the same depth like in game
+    visible_faces.sort_by(|visible_face_1, visible_face_2| visible_face_1.depth.total_cmp(&visible_face_2.depth));
+
+    for visible_face in &visible_faces {
+        textures.entry(visible_face.texture_name.to_string())
+            .or_insert_with(|| texture_source.load_texture(visible_face.texture_name))
+            .as_ref()?;
+    }
+
+    let resolution = visible_faces.iter()
+        .filter_map(|visible_face| textures[visible_face.texture_name].as_ref())
+        .map(|texture| texture.width())
+        .fold(16, u32::max);
+    let pixel_size = 16.0 / resolution as f32;
+
+    let mut tile = RgbaImage::new(resolution, resolution);
+
+    for visible_face in visible_faces {
+        let texture = textures[visible_face.texture_name].as_ref()?;
+
+        let [min_x, min_y, max_x, max_y] = visible_face.screen_bounds;
+
+        for (x, y, pixel) in tile.enumerate_pixels_mut() {
+            // Screen Y points downwards while model Y points upwards
+            let [screen_x, screen_y] = [(x as f32 + 0.5) * pixel_size, 16.0 - (y as f32 + 0.5) * pixel_size];
+
+            if screen_x < min_x || screen_x > max_x || screen_y < min_y || screen_y > max_y {
+                continue;
+            }
+
+            let model_point = from_screen([screen_x, screen_y, visible_face.depth], visible_face.variant, orientation);
+            let [u, v] = texture_coordinates(&visible_face, model_point);
+
+            let texture_pixel = texture.get_pixel(
+                ((u / 16.0 * texture.width() as f32) as u32).min(texture.width() - 1),
+                ((v / 16.0 * texture.height() as f32) as u32).min(texture.height() - 1)
+            );
+
+            if texture_pixel[3] > 0 {
+                *pixel = match (visible_face.face.tintindex, tint_color) {
+                    (Some(_), Some(tint_color)) => Rgba([0, 1, 2, 3].map(|index| match index {
+                        3 => texture_pixel[3],
+                        _ => (texture_pixel[index] as u32 * tint_color[index] as u32 / 255) as u8,
+                    })),
+                    _ => texture_pixel,
+                };
+            }
+        }
+    }
+
+    if tile.pixels().all(|pixel| pixel[3] == 0) {
+        return None;
+    }
+
+    Some(tile.into())
+}
+
+/// Texture coordinates in model units, 0 to 16 across the texture, of the point on a face, honoring the face's UV and rotation.
+fn texture_coordinates(visible_face: &VisibleFace, model_point: [f32; 3]) -> [f32; 2] {
+    let [u, v] = default_uv(visible_face.face_name, model_point);
+
+    let [from_u, from_v] = default_uv(visible_face.face_name, visible_face.element.from);
+    let [to_u, to_v] = default_uv(visible_face.face_name, visible_face.element.to);
+    let default_uv_bounds = [from_u.min(to_u), from_v.min(to_v), from_u.max(to_u), from_v.max(to_v)];
+
+    // Position on the face from 0 to 1, turned by the face's clockwise texture rotation
+    let relative_position = [
+        (u - default_uv_bounds[0]) / (default_uv_bounds[2] - default_uv_bounds[0]).max(f32::EPSILON),
+        (v - default_uv_bounds[1]) / (default_uv_bounds[3] - default_uv_bounds[1]).max(f32::EPSILON),
+    ];
+    let [s, t] = (0..visible_face.face.rotation / 90 % 4).fold(relative_position, |[s, t], _| [t, 1.0 - s]);
+
+    let [from_u, from_v, to_u, to_v] = visible_face.face.uv.unwrap_or(default_uv_bounds);
+
+    [from_u + s * (to_u - from_u), from_v + t * (to_v - from_v)]
+}
+
+/// Texture coordinates the game derives from a point's position on a face if the face has no explicit UV.
+fn default_uv(face_name: &str, [x, y, z]: [f32; 3]) -> [f32; 2] {
+    match face_name {
+        "north" => [16.0 - x, 16.0 - y],
+        "south" => [x, 16.0 - y],
+        "east" => [16.0 - z, 16.0 - y],
+        "west" => [z, 16.0 - y],
+        "up" => [x, z],
+        _ => [x, 16.0 - z],
+    }
+}
+
+/// Loads a model and its parents into the model cache, returning the names of the chain starting at the model itself.
+/// `None` if the chain is too long.
+fn load_model_chain(texture_source: &TextureSource, models: &mut HashMap<String, Option<Model>>, model_name: &str) -> eyre::Result<Option<Vec<String>>> {
+    let mut model_chain = vec![];
+    let mut next_model_name = Some(normalize_resource_name(model_name).to_string());
+
+    while let Some(model_name) = next_model_name {
+        if model_chain.len() == MAX_REFERENCE_DEPTH {
+            return Ok(None);
+        }
+
+        if !models.contains_key(&model_name) {
+            // Built-in models such as `builtin/generated` have no file
+            let model = match model_name.strip_prefix("block/") {
+                Some(block_model_name) => read_json::<Model>(texture_source, &format!("models/block/{block_model_name}.json"))?,
+                None => None,
+            };
+
+            models.insert(model_name.clone(), model);
+        }
+
+        next_model_name = models[&model_name].as_ref()
+            .and_then(|model| model.parent.as_deref())
+            .map(|parent| normalize_resource_name(parent).to_string());
+
+        model_chain.push(model_name);
+    }
+
+    Ok(Some(model_chain))
+}
+
+/// Merges the texture variables of a loaded model chain and takes the elements of the closest model defining any. `None` if
+/// none of the models exist.
+fn resolve_model<'a>(models: &'a HashMap<String, Option<Model>>, model_chain: &[String]) -> Option<ResolvedModel<'a>> {
+    let model_chain = model_chain.iter().filter_map(|model_name| models[model_name].as_ref()).collect::<Vec<_>>();
+
+    if model_chain.is_empty() {
+        return None;
+    }
+
+    Some(ResolvedModel {
+        // Texture variables of children override those of their parents
+        textures: model_chain.iter().rev()
+            .flat_map(|model| model.textures.iter().map(|(name, value)| (name.as_str(), value.as_str())))
+            .collect(),
+        elements: model_chain.iter().find_map(|model| model.elements.as_deref()).unwrap_or(&[]),
+    })
+}
+
+/// Follows texture variables like `#side` to the name of a block texture. `None` if a variable is undefined or the
+/// texture is not a block texture.
+fn resolve_texture_name<'a>(textures: &HashMap<&'a str, &'a str>, mut texture_reference: &'a str) -> Option<&'a str> {
+    for _ in 0..MAX_REFERENCE_DEPTH {
+        match texture_reference.strip_prefix('#') {
+            Some(variable_name) => texture_reference = textures.get(variable_name)?,
+            None => return normalize_resource_name(texture_reference).strip_prefix("block/"),
+        }
+    }
+
+    None
+}
+
+fn normalize_resource_name(resource_name: &str) -> &str {
+    resource_name.strip_prefix("minecraft:").unwrap_or(resource_name)
+}
+
+/// Rotates a vector like a block state's `x` rotation, which turns north to down at 90 degrees.
+fn rotate_x<T: Copy + Neg<Output = T>>(vector: [T; 3], degrees: i32) -> [T; 3] {
+    (0..degrees.rem_euclid(360) / 90).fold(vector, |[x, y, z], _| [x, z, -y])
+}
+
+/// Rotates a vector like a block state's `y` rotation, which turns north to east at 90 degrees.
+fn rotate_y<T: Copy + Neg<Output = T>>(vector: [T; 3], degrees: i32) -> [T; 3] {
+    (0..degrees.rem_euclid(360) / 90).fold(vector, |[x, y, z], _| [-z, y, x])
+}
+
+/// Rotates a point of a model around the block's center like the variant's rotation and moves it into the viewer's frame,
+/// with X pointing right, Y up and Z towards the viewer.
+fn to_screen(point: [f32; 3], variant: &Variant, orientation: Orientation) -> [f32; 3] {
+    orientation.unrotate(rotate_y(rotate_x(point.map(|coordinate| coordinate - 8.0), variant.x), variant.y)).map(|coordinate| coordinate + 8.0)
+}
+
+/// Reverts [`to_screen`].
+fn from_screen(point: [f32; 3], variant: &Variant, orientation: Orientation) -> [f32; 3] {
+    rotate_x(rotate_y(orientation.rotate(point.map(|coordinate| coordinate - 8.0)), -variant.y), -variant.x).map(|coordinate| coordinate + 8.0)
+}
\ No newline at end of file
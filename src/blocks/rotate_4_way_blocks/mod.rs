@@ -1,21 +1,20 @@
 use std::collections::HashMap;
 
-use camino::Utf8Path;
 use color_eyre::eyre;
 use image::imageops;
 use once_cell::sync::Lazy;
 
 use crate::blocks;
-use crate::blocks::TextureWithBlockState;
+use crate::blocks::{TextureSource, TextureWithBlockState};
 use crate::cli_arguments::TextureFilteringMode;
 
 pub static ROTATE_4_WAY_BLOCKS: Lazy<Vec<&'static str>> = Lazy::new(|| {
     include_str!("blocks.txt").lines().collect::<Vec<_>>()
 });
 
-pub fn get_rotate_4_way_textures(texture_filtering_mode: &TextureFilteringMode, block_textures_path: &Utf8Path) -> eyre::Result<HashMap<String, TextureWithBlockState>> {
+pub fn get_rotate_4_way_textures(texture_filtering_mode: &TextureFilteringMode, texture_source: &TextureSource) -> eyre::Result<HashMap<String, TextureWithBlockState>> {
     Ok(
-        blocks::get_block_textures(texture_filtering_mode, block_textures_path, &ROTATE_4_WAY_BLOCKS).into_iter()
+        blocks::get_block_textures(texture_filtering_mode, texture_source, &ROTATE_4_WAY_BLOCKS).into_iter()
             .flat_map(|(name, TextureWithBlockState { texture, block_id, .. })| {
                 (0..4)
                     .map(|i| {
@@ -38,4 +37,4 @@ pub fn get_rotate_4_way_textures(texture_filtering_mode: &TextureFilteringMode,
             })
             .collect::<HashMap<_, _>>()
     )
-}
+}
\ No newline at end of file
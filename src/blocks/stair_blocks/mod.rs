@@ -1,12 +1,11 @@
 use std::collections::HashMap;
 
-use camino::Utf8Path;
 use color_eyre::eyre;
 use image::Rgba;
 use once_cell::sync::Lazy;
 
 use crate::blocks;
-use crate::blocks::TextureWithBlockState;
+use crate::blocks::{TextureSource, TextureWithBlockState};
 use crate::cli_arguments::TextureFilteringMode;
 use crate::helpers::FillPixels;
 
@@ -14,14 +13,15 @@ pub static STAIR_BLOCKS: Lazy<Vec<&'static str>> = Lazy::new(|| {
     include_str!("blocks.txt").lines().collect::<Vec<_>>()
 });
 
-pub fn get_stair_block_textures(texture_filtering_mode: &TextureFilteringMode, block_textures_path: &Utf8Path) -> eyre::Result<HashMap<String, TextureWithBlockState>> {
+pub fn get_stair_block_textures(texture_filtering_mode: &TextureFilteringMode, texture_source: &TextureSource) -> eyre::Result<HashMap<String, TextureWithBlockState>> {
     Ok(
-        blocks::get_block_textures(texture_filtering_mode, block_textures_path, &STAIR_BLOCKS).into_iter()
+        blocks::get_block_textures(texture_filtering_mode, texture_source, &STAIR_BLOCKS).into_iter()
             .flat_map(|(name, TextureWithBlockState { texture, block_id, .. })| {
                 (0..4)
                     .map(|i| {
                         let mut texture = texture.clone();
-                        texture.fill_pixels((i % 2) * 8, (i / 2) * 8, 8, 8, Rgba([0, 0, 0, 0]));
+                        let half = texture.width() / 2;
+                        texture.fill_pixels((i % 2) * half, (i / 2) * half, half, half, Rgba([0, 0, 0, 0]));
 
                         (format!("{}_stair_{}", &name, i * 90), TextureWithBlockState {
                             texture,
@@ -0,0 +1,277 @@
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use color_eyre::eyre;
+use color_eyre::eyre::WrapErr;
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+use serde::Deserialize;
+use zip::ZipArchive;
+
+use crate::cli_arguments::AnimatedTextureMode;
+
+/// Location of the vanilla assets inside the Minecraft client JAR and resource packs.
+const ASSETS_PATH: &str = "assets/minecraft/";
+
+/// Location of block textures relative to [`ASSETS_PATH`].
+const BLOCK_TEXTURES_PATH: &str = "textures/block/";
+
+/// Asset folders relative to [`ASSETS_PATH`] that are read from archives, everything else is skipped.
+const ARCHIVE_ASSET_FOLDERS: [&str; 4] = [BLOCK_TEXTURES_PATH, "textures/colormap/", "blockstates/", "models/block/"];
+
+/// Where block textures and models are loaded from, one or more layers in increasing priority, e.g. the vanilla client JAR
+/// followed by resource packs. Assets missing from a layer fall back to the layers below it, just like resource packs in game.
+pub struct TextureSource {
+    layers: Vec<TextureLayer>,
+    animated_texture_mode: AnimatedTextureMode,
+}
+
+/// A single source of block assets.
+enum TextureLayer {
+    /// Extracted `assets/minecraft/textures/block` folder, which only holds textures.
+    TextureDirectory(Utf8PathBuf),
+    /// Extracted client JAR or resource pack, pointing at its `assets/minecraft` folder.
+    AssetDirectory(Utf8PathBuf),
+    /// File contents by path relative to [`ASSETS_PATH`], read once up front so assets can be loaded without holding on to
+    /// the archive.
+    Archive {
+        archive_path: Utf8PathBuf,
+        assets: HashMap<String, Vec<u8>>,
+    },
+}
+
+impl TextureSource {
+    /// Opens all given texture folders and archives, the last one taking priority.
+    pub fn open(paths: &[Utf8PathBuf], animated_texture_mode: AnimatedTextureMode) -> eyre::Result<Self> {
+        Ok(Self {
+            layers: paths.iter().map(|path| TextureLayer::open(path)).collect::<eyre::Result<Vec<_>>>()?,
+            animated_texture_mode,
+        })
+    }
+
+    /// Loads a block texture by name from the highest priority layer that has it, cropped to a square at its native
+    /// resolution. Animated textures are reduced to a single frame according to the animated texture mode, `None` if they
+    /// are excluded.
+    pub fn load_texture(&self, texture_name: &str) -> Option<DynamicImage> {
+        let asset_path = format!("{BLOCK_TEXTURES_PATH}{texture_name}.png");
+
+        let Some((layer, bytes)) = self.layers.iter().rev().find_map(|layer| Some((layer, layer.read_asset(&asset_path)?))) else {
+            tracing::warn!("Unable to find texture '{}' in any block textures path.", texture_name);
+            return None;
+        };
+
+        let texture = match image::load_from_memory(&bytes) {
+            Ok(texture) => texture,
+            Err(e) => {
+                tracing::warn!("Unable to read texture '{}' in '{}': {e}", texture_name, layer.path());
+                return None;
+            }
+        };
+
+        // The animation is defined by the metadata next to the texture in the same layer
+        let animation = layer.read_asset(&format!("{asset_path}.mcmeta"))
+            .and_then(|bytes| match serde_json::from_slice::<TextureMetadata>(&bytes) {
+                Ok(texture_metadata) => texture_metadata.animation,
+                Err(e) => {
+                    tracing::warn!("Unable to read animation of texture '{}' in '{}': {e}", texture_name, layer.path());
+                    None
+                }
+            });
+
+        let texture = match (animation, self.animated_texture_mode) {
+            (None, _) => texture,
+            (Some(_), AnimatedTextureMode::Exclude) => return None,
+            (Some(animation), AnimatedTextureMode::FirstFrame) => animation_frames(&texture, &animation).into_iter().next()?.0,
+            (Some(animation), AnimatedTextureMode::Average) => average_frames(&animation_frames(&texture, &animation))?,
+        };
+
+        let resolution = texture.width().min(texture.height());
+
+        Some(texture.crop_imm(0, 0, resolution, resolution))
+    }
+
+    /// Reads an asset by its path relative to `assets/minecraft`, e.g. `blockstates/stone.json`, from the highest priority
+    /// layer that has it.
+    pub fn read_asset(&self, asset_path: &str) -> Option<Cow<'_, [u8]>> {
+        self.layers.iter().rev().find_map(|layer| layer.read_asset(asset_path))
+    }
+
+    /// File names of all assets in a folder relative to `assets/minecraft`, e.g. `blockstates/`, across all layers.
+    pub fn asset_names(&self, folder: &str) -> BTreeSet<String> {
+        self.layers.iter().flat_map(|layer| layer.asset_names(folder)).collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct TextureMetadata {
+    animation: Option<Animation>,
+}
+
+#[derive(Deserialize)]
+struct Animation {
+    #[serde(default = "default_frame_time")]
+    frametime: u32,
+    frames: Option<Vec<AnimationFrame>>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnimationFrame {
+    Index(u32),
+    Timed {
+        index: u32,
+        time: u32,
+    },
+}
+
+fn default_frame_time() -> u32 {
+    1
+}
+
+/// Frames of an animated texture in the order they are shown, each with the number of ticks it is shown for. Frames are
+/// square unless the animation defines their size, and are laid out from left to right, then top to bottom.
+fn animation_frames(texture: &DynamicImage, animation: &Animation) -> Vec<(DynamicImage, u32)> {
+    let frame_width = animation.width.unwrap_or(texture.width().min(texture.height())).max(1);
+    let frame_height = animation.height.unwrap_or(texture.width().min(texture.height())).max(1);
+
+    let frames_per_row = texture.width() / frame_width;
+    let frame_count = frames_per_row * (texture.height() / frame_height);
+
+    let frames = match &animation.frames {
+        Some(frames) => frames.iter()
+            .map(|frame| match *frame {
+                AnimationFrame::Index(index) => (index, animation.frametime),
+                AnimationFrame::Timed { index, time } => (index, time),
+            })
+            .filter(|&(index, _)| index < frame_count)
+            .collect(),
+        None => (0..frame_count).map(|index| (index, animation.frametime)).collect::<Vec<_>>(),
+    };
+
+    frames.into_iter()
+        .map(|(index, time)| (
+            texture.crop_imm((index % frames_per_row) * frame_width, (index / frames_per_row) * frame_height, frame_width, frame_height),
+            time
+        ))
+        .collect()
+}
+
+/// Blends all frames of an animation weighted by how long each of them is shown. `None` if there are no frames.
+fn average_frames(frames: &[(DynamicImage, u32)]) -> Option<DynamicImage> {
+    let (first_frame, _) = frames.first()?;
+    let total_time = frames.iter().map(|(_, time)| *time as u64).sum::<u64>().max(1);
+
+    let mut channel_sums = vec![[0u64; 4]; (first_frame.width() * first_frame.height()) as usize];
+
+    for (frame, time) in frames {
+        for (x, y, pixel) in frame.pixels() {
+            let channel_sum = &mut channel_sums[(y * first_frame.width() + x) as usize];
+
+            for index in 0..4 {
+                channel_sum[index] += pixel[index] as u64 * *time as u64;
+            }
+        }
+    }
+
+    Some(RgbaImage::from_fn(first_frame.width(), first_frame.height(), |x, y| {
+        Rgba(channel_sums[(y * first_frame.width() + x) as usize].map(|channel_sum| (channel_sum / total_time) as u8))
+    }).into())
+}
+
+impl TextureLayer {
+    fn open(path: &Utf8Path) -> eyre::Result<Self> {
+        if path.is_dir() {
+            let assets_path = path.join(ASSETS_PATH);
+
+            return Ok(if assets_path.is_dir() {
+                TextureLayer::AssetDirectory(assets_path)
+            } else {
+                TextureLayer::TextureDirectory(path.to_path_buf())
+            });
+        }
+
+        let file = File::open(path).wrap_err_with(|| format!("Unable to open block textures path '{path}'."))?;
+        let mut archive = ZipArchive::new(file).wrap_err_with(|| format!("'{path}' is neither a folder nor a .jar/.zip archive."))?;
+
+        let mut assets = HashMap::new();
+
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+
+            let Some(asset_path) = entry.name()
+                .strip_prefix(ASSETS_PATH)
+                .filter(|asset_path| ARCHIVE_ASSET_FOLDERS.iter().any(|folder| asset_path.starts_with(folder)))
+                .map(|asset_path| asset_path.to_string()) else {
+                continue;
+            };
+
+            let mut bytes = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut bytes)?;
+
+            assets.insert(asset_path, bytes);
+        }
+
+        tracing::info!(
+            "Found {} block texture(s) in '{}'.",
+            assets.keys().filter(|asset_path| asset_path.starts_with(BLOCK_TEXTURES_PATH)).count(),
+            path
+        );
+
+        Ok(TextureLayer::Archive {
+            archive_path: path.to_path_buf(),
+            assets,
+        })
+    }
+
+    fn path(&self) -> &Utf8Path {
+        match self {
+            TextureLayer::TextureDirectory(path) | TextureLayer::AssetDirectory(path) => path,
+            TextureLayer::Archive { archive_path, .. } => archive_path,
+        }
+    }
+
+    /// Reads an asset by its path relative to [`ASSETS_PATH`], or `None` if this layer does not contain it.
+    fn read_asset(&self, asset_path: &str) -> Option<Cow<'_, [u8]>> {
+        match self {
+            TextureLayer::TextureDirectory(block_textures_path) => {
+                let file_path = match asset_path.strip_prefix(BLOCK_TEXTURES_PATH) {
+                    Some(file_name) => block_textures_path.join(file_name),
+                    // Other textures such as colormaps are looked for next to the block textures folder
+                    None => block_textures_path.parent()?.join(asset_path.strip_prefix("textures/")?),
+                };
+
+                fs::read(file_path).ok().map(Cow::Owned)
+            }
+            TextureLayer::AssetDirectory(assets_path) => fs::read(assets_path.join(asset_path)).ok().map(Cow::Owned),
+            TextureLayer::Archive { assets, .. } => assets.get(asset_path).map(|bytes| Cow::Borrowed(bytes.as_slice())),
+        }
+    }
+
+    fn asset_names(&self, folder: &str) -> Vec<String> {
+        let directory_path = match self {
+            TextureLayer::TextureDirectory(block_textures_path) if folder == BLOCK_TEXTURES_PATH => block_textures_path.clone(),
+            TextureLayer::TextureDirectory(_) => return vec![],
+            TextureLayer::AssetDirectory(assets_path) => assets_path.join(folder),
+            TextureLayer::Archive { assets, .. } => {
+                return assets.keys()
+                    .filter_map(|asset_path| asset_path.strip_prefix(folder))
+                    .filter(|file_name| !file_name.contains('/'))
+                    .map(|file_name| file_name.to_string())
+                    .collect();
+            }
+        };
+
+        directory_path.read_dir_utf8()
+            .map(|entries| entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .map(|entry| entry.file_name().to_string())
+                .collect())
+            .unwrap_or_default()
+    }
+}
\ No newline at end of file
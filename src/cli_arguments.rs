@@ -2,18 +2,23 @@ use std::str::FromStr;
 
 use camino::Utf8PathBuf;
 
+use crate::biomes::Biome;
+use crate::{block_metadata, block_physics};
+use crate::minecraft_versions::MinecraftVersion;
+use crate::palette_presets::{BlockPattern, PalettePreset};
+
 #[derive(gumdrop::Options)]
 pub struct CliArguments {
     pub help: bool,
 
-    #[options(help = "Path of an extracted <Minecraft JAR>/assets/minecraft/textures/block folder.", short = "t", meta = "<PATH>", required)]
-    pub block_textures_path: Utf8PathBuf,
+    #[options(help = "Path of an extracted <Minecraft JAR>/assets/minecraft/textures/block folder, or of a Minecraft client .jar or resource pack .zip to read the block textures from. Can be given multiple times to layer resource packs over the vanilla textures, later paths overriding earlier ones. Not needed for map art.", long = "block-textures-path", short = "t", meta = "<PATH>")]
+    pub block_textures_paths: Vec<Utf8PathBuf>,
 
     #[options(help = "Image to be processed.", short = "i", meta = "<PATH/URL>", required)]
     pub input_image_path: String,
 
-    #[options(help = "Path to desired output. For schematic output use .schematic or .litematic. Everything else is interpreted as image output.", short = "o", meta = "<PATH>", required)]
-    pub output_path: Utf8PathBuf,
+    #[options(help = "Path to desired output. For schematic output use .litematic, .schem (Sponge), .schematic (legacy MCEdit, pre-1.13 blocks only), .nbt (vanilla structure block, split into pieces if larger than 48 blocks), .mcstructure (Bedrock Edition structure), .mcfunction (setblock/fill commands) or .zip (datapack with those commands). Everything else is interpreted as image output.", short = "o", meta = "<PATH>")]
+    pub output_path: Option<Utf8PathBuf>,
 
     #[options(help = "The width of the output Minecraft structure in blocks.", short = "w", meta = "<BLOCKS>")]
     pub block_width: Option<usize>,
@@ -27,11 +32,80 @@ pub struct CliArguments {
     #[options(help = "What dithering matrix to use. Options: JarvisJudiceNinke, FloydSteinberg", meta = "<ALGORITHM>", default = "JarvisJudiceNinke")]
     pub dithering_matrix: DitheringMatrix,
 
+    #[options(help = "Formula to compare the colors of the image and the blocks with. CIE76 is the fastest, CIEDE2000 and CAM16-UCS match human perception best, CMC uses a 1:1 ratio. Options: cie76, cie94, cmc, de2000, oklab, cam16-ucs", no_short, meta = "<METRIC>", default = "de2000")]
+    pub color_metric: ColorMetric,
+
+    #[options(help = "Color that transparent parts of the image and of blocks like slabs are seen against, as a hex code like #87CEEB. By default they are compared over both black and white, matching transparent pixels with transparent blocks.", no_short, meta = "<COLOR>")]
+    pub background_color: Option<BackgroundColor>,
+
     #[options(help = "Exclude blocks that cannot be obtained in survival mode.", short = "s", default = "false")]
     pub exclude_non_survival_blocks: bool,
 
-    #[options(help = "Limit the block palette to the provided textures. Takes precedent over exclude-non-survival-blocks.", short = "p")]
+    #[options(help = "Exclude blocks that cannot be farmed or otherwise obtained indefinitely in survival mode, such as ores and deepslate.", no_short)]
+    pub renewable_blocks_only: bool,
+
+    #[options(help = "Exclude blocks that are harder to come by than this, from 1 (gathered in bulk, e.g. dirt or logs) over 2 (simple crafting or smelting), 3 (farms or ocean monuments) and 4 (rare materials) to 5 (e.g. diamond blocks).", no_short, meta = "<COST>")]
+    pub max_block_cost: Option<u32>,
+
+    #[options(help = "Exclude blocks that can only be collected with a Silk Touch tool.", no_short)]
+    pub exclude_silk_touch_blocks: bool,
+
+    #[options(help = "Exclude blocks that need to be gathered in the Nether or the End.", no_short)]
+    pub overworld_blocks_only: bool,
+
+    #[options(help = "How much color error (as measured by color-metric, per chunk) a block may add for each point of cost it saves. 0 always uses the closest matching block, higher values prefer cheaper blocks.", no_short, meta = "<WEIGHT>", default = "0")]
+    pub cost_weight: f32,
+
+    #[options(help = "File with one <BLOCK ID>|<COST> line per block, overriding the built-in block costs used by cost-weight.", no_short, meta = "<PATH>")]
+    pub block_costs_path: Option<Utf8PathBuf>,
+
+    #[options(help = "File with one <BLOCK ID>|<COUNT> line per available block, e.g. the contents of a storage system. Once a block runs out the next best one is used instead, blocks that are not listed are not used at all.", no_short, meta = "<PATH>")]
+    pub material_budget_path: Option<Utf8PathBuf>,
+
+    #[options(help = "Limit the block palette to the provided textures. Takes precedent over the survival and cost filters, exclusions and presets still apply.", short = "p")]
     pub block_palette: Option<BlockPalette>,
+
+    #[options(help = "Leave these blocks out of the palette, as comma separated block IDs or patterns like *_glazed_terracotta.", no_short, meta = "<BLOCKS>")]
+    pub exclude: Option<BlockPatterns>,
+
+    #[options(help = "Built-in block selections to apply to the palette, comma separated. Options: concrete-and-wool, no-gravity-blocks, no-light-sources, no-tile-entities", no_short, meta = "<PRESETS>")]
+    pub palette_presets: Option<PalettePresets>,
+
+    #[options(help = "How to handle blocks that fall, break or die on their own, such as sand, carpets or coral. Either place falling and attached blocks only on top of blocks that hold them, exclude them, or allow them anywhere. Floors and map art lie on the ground, which holds them anywhere. Blocks that need water or space around them are excluded unless allowed. Options: supported, exclude, allow", no_short, meta = "<MODE>", default = "supported")]
+    pub unstable_blocks: UnstableBlockMode,
+
+    #[options(help = "How to lay out the result. Walls are named after the direction they face, floors are read from above and ceilings from below, both with the top of the image facing north. Ignored for map art. Options: south, north, east, west, floor, ceiling", meta = "<ORIENTATION>", default = "south")]
+    pub orientation: Orientation,
+
+    #[options(help = "Generate map art using Minecraft's map colors instead of block textures, with one pixel per block and laid out as a floor. Staircased map art places blocks at different heights to use all shades of each color. Options: flat, staircased", no_short, meta = "<MODE>")]
+    pub map_art: Option<MapArtMode>,
+
+    #[options(help = "How to handle animated block textures like magma or sea lanterns. Either use their first frame, blend all frames weighted by how long each is shown, or exclude them. Options: first-frame, average, exclude", meta = "<MODE>", default = "first-frame")]
+    pub animated_texture_mode: AnimatedTextureMode,
+
+    #[options(help = "Biome to color grass, leaves, vines and water for, as they only look right there. Only applies to block textures read from a Minecraft client .jar or resource pack with block models and colormaps. Options: plains, forest, swamp, ... (any Java Edition biome)", meta = "<BIOME>", default = "plains")]
+    pub biome: Biome,
+
+    #[options(help = "Minecraft Java Edition version to build for. Sets the data version of schematics and structures and leaves out blocks that do not exist in that version, using the old names of renamed blocks. Options: 1.16.5, 1.17.1, 1.18.2, 1.19.2, 1.19.4, 1.20.1, 1.20.2, 1.20.4, 1.20.6, 1.21.1, 1.21.3, 1.21.4, or e.g. 1.20.x for the latest of those", no_short, meta = "<VERSION>", default = "1.20.1")]
+    pub minecraft_version: MinecraftVersion,
+
+    #[options(help = "Sponge schematic version to write for .schem output. Options: 2, 3", meta = "<VERSION>", default = "2")]
+    pub sponge_schematic_version: SpongeSchematicVersion,
+
+    #[options(help = "Leave blocks that do not exist on Bedrock Edition out of the palette for .mcstructure output instead of failing on them.", no_short)]
+    pub exclude_non_bedrock_blocks: bool,
+
+    #[options(help = "Maximum number of commands per function for .mcfunction and datapack output. Keep this at or below the maxCommandChainLength game rule.", short = "m", meta = "<NUMBER>", default = "65536")]
+    pub max_commands_per_function: usize,
+
+    #[options(help = "Place the result directly into this singleplayer world folder (Minecraft 1.18+). Close the world in the game first.", meta = "<PATH>")]
+    pub world_path: Option<Utf8PathBuf>,
+
+    #[options(help = "World position of the lowest north-western block of the result when placing it into a world.", meta = "<X,Y,Z>", default = "0,64,0")]
+    pub world_origin: WorldOrigin,
+
+    #[options(help = "Dimension to place the result in when placing it into a world. Options: overworld, the_nether, the_end", meta = "<DIMENSION>", default = "overworld")]
+    pub world_dimension: Dimension,
 }
 
 pub enum DitheringMatrix {
@@ -67,6 +141,129 @@ impl FromStr for DitheringMatrix {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum Orientation {
+    WallSouth,
+    WallNorth,
+    WallEast,
+    WallWest,
+    Floor,
+    Ceiling,
+}
+
+impl FromStr for Orientation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "south" => Ok(Self::WallSouth),
+            "north" => Ok(Self::WallNorth),
+            "east" => Ok(Self::WallEast),
+            "west" => Ok(Self::WallWest),
+            "floor" => Ok(Self::Floor),
+            "ceiling" => Ok(Self::Ceiling),
+            _ => Err("Invalid orientation.")
+        }
+    }
+}
+
+pub enum MapArtMode {
+    Flat,
+    Staircased,
+}
+
+impl FromStr for MapArtMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(Self::Flat),
+            "staircased" => Ok(Self::Staircased),
+            _ => Err("Invalid map art mode.")
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum AnimatedTextureMode {
+    FirstFrame,
+    Average,
+    Exclude,
+}
+
+impl FromStr for AnimatedTextureMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-frame" => Ok(Self::FirstFrame),
+            "average" => Ok(Self::Average),
+            "exclude" => Ok(Self::Exclude),
+            _ => Err("Invalid animated texture mode.")
+        }
+    }
+}
+
+pub enum SpongeSchematicVersion {
+    V2,
+    V3,
+}
+
+impl FromStr for SpongeSchematicVersion {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "2" => Ok(Self::V2),
+            "3" => Ok(Self::V3),
+            _ => Err("Invalid Sponge schematic version.")
+        }
+    }
+}
+
+pub struct WorldOrigin(pub [i32; 3]);
+
+impl FromStr for WorldOrigin {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split(',').map(|coordinate| coordinate.trim().parse::<i32>()).collect::<Result<Vec<_>, _>>() {
+            Ok(coordinates) if coordinates.len() == 3 => Ok(Self([coordinates[0], coordinates[1], coordinates[2]])),
+            _ => Err("Invalid world origin, expected <X,Y,Z>.")
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+    End,
+}
+
+impl Dimension {
+    pub fn region_directory(&self) -> &'static str {
+        match self {
+            Dimension::Overworld => "region",
+            Dimension::Nether => "DIM-1/region",
+            Dimension::End => "DIM1/region",
+        }
+    }
+}
+
+impl FromStr for Dimension {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "overworld" => Ok(Self::Overworld),
+            "the_nether" => Ok(Self::Nether),
+            "the_end" => Ok(Self::End),
+            _ => Err("Invalid dimension.")
+        }
+    }
+}
+
 pub struct BlockPalette(pub Vec<String>);
 
 impl FromStr for BlockPalette {
@@ -77,7 +274,148 @@ impl FromStr for BlockPalette {
     }
 }
 
-pub enum TextureFilteringMode {
-    AllowList(Vec<String>),
-    BlockList(Vec<String>),
+#[derive(Clone, Copy)]
+pub enum ColorMetric {
+    Cie76,
+    Cie94,
+    Cmc,
+    De2000,
+    Oklab,
+    Cam16Ucs,
+}
+
+impl FromStr for ColorMetric {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cie76" => Ok(Self::Cie76),
+            "cie94" => Ok(Self::Cie94),
+            "cmc" => Ok(Self::Cmc),
+            "de2000" => Ok(Self::De2000),
+            "oklab" => Ok(Self::Oklab),
+            "cam16-ucs" => Ok(Self::Cam16Ucs),
+            _ => Err("Invalid color metric.")
+        }
+    }
+}
+
+pub struct BackgroundColor(pub [u8; 3]);
+
+impl FromStr for BackgroundColor {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex_code = s.strip_prefix('#').unwrap_or(s);
+
+        match u32::from_str_radix(hex_code, 16) {
+            Ok(color) if hex_code.len() == 6 => Ok(Self([(color >> 16) as u8, (color >> 8) as u8, color as u8])),
+            _ => Err("Invalid background color, expected a hex code like #87CEEB.")
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum UnstableBlockMode {
+    Supported,
+    Exclude,
+    Allow,
+}
+
+impl FromStr for UnstableBlockMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "supported" => Ok(Self::Supported),
+            "exclude" => Ok(Self::Exclude),
+            "allow" => Ok(Self::Allow),
+            _ => Err("Invalid unstable block mode.")
+        }
+    }
+}
+
+pub struct BlockPatterns(pub Vec<BlockPattern>);
+
+impl FromStr for BlockPatterns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.split(',').map(|block_pattern| BlockPattern::new(block_pattern.trim())).collect()))
+    }
+}
+
+pub struct PalettePresets(pub Vec<PalettePreset>);
+
+impl FromStr for PalettePresets {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.split(',').map(|palette_preset| palette_preset.trim().parse()).collect::<Result<_, _>>()?))
+    }
+}
+
+/// Which blocks may be part of the palette. Blocks are picked from the allow list if given or by their metadata otherwise,
+/// then narrowed down by the exclusions, presets and whether they stay in place in the chosen layout.
+pub struct TextureFilteringMode {
+    allow_list: Option<Vec<String>>,
+    requirements: BlockRequirements,
+    exclusions: Vec<BlockPattern>,
+    palette_presets: Vec<PalettePreset>,
+    unstable_block_mode: UnstableBlockMode,
+}
+
+impl TextureFilteringMode {
+    pub fn from_cli_arguments(cli_arguments: &CliArguments) -> Self {
+        Self {
+            allow_list: cli_arguments.block_palette.as_ref().map(|block_palette| block_palette.0.clone()),
+            requirements: BlockRequirements::from_cli_arguments(cli_arguments),
+            exclusions: cli_arguments.exclude.as_ref().map(|exclude| exclude.0.clone()).unwrap_or_default(),
+            palette_presets: cli_arguments.palette_presets.as_ref().map(|palette_presets| palette_presets.0.clone()).unwrap_or_default(),
+            unstable_block_mode: block_physics::unstable_block_mode(cli_arguments),
+        }
+    }
+
+    pub fn allows(&self, block_id: &str) -> bool {
+        let is_selected = match &self.allow_list {
+            Some(allowed_block_ids) => allowed_block_ids.iter().any(|allowed_block_id| allowed_block_id == block_id),
+            None => self.requirements.allows(block_id),
+        };
+
+        is_selected
+            && !self.exclusions.iter().any(|exclusion| exclusion.matches(block_id))
+            && self.palette_presets.iter().all(|palette_preset| palette_preset.allows(block_id))
+            && block_physics::is_allowed(block_id, self.unstable_block_mode)
+    }
+}
+
+/// Restrictions on the survival availability and cost of palette blocks, see `block_metadata.txt`.
+struct BlockRequirements {
+    obtainable: bool,
+    renewable: bool,
+    max_cost: Option<u32>,
+    without_silk_touch: bool,
+    overworld_only: bool,
+}
+
+impl BlockRequirements {
+    fn from_cli_arguments(cli_arguments: &CliArguments) -> Self {
+        Self {
+            obtainable: cli_arguments.exclude_non_survival_blocks,
+            renewable: cli_arguments.renewable_blocks_only,
+            max_cost: cli_arguments.max_block_cost,
+            without_silk_touch: cli_arguments.exclude_silk_touch_blocks,
+            overworld_only: cli_arguments.overworld_blocks_only,
+        }
+    }
+
+    fn allows(&self, block_id: &str) -> bool {
+        let block_metadata = block_metadata::block_metadata(block_id);
+
+        (!self.obtainable || block_metadata.obtainable)
+            && (!self.renewable || block_metadata.renewable)
+            && self.max_cost.is_none_or(|max_cost| block_metadata.cost <= max_cost)
+            && (!self.without_silk_touch || !block_metadata.requires_silk_touch)
+            && (!self.overworld_only || block_metadata.dimension == Dimension::Overworld)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,197 @@
+use std::f32::consts::PI;
+use std::slice;
+
+use image::Rgba;
+use lab::Lab;
+use once_cell::sync::Lazy;
+
+use crate::cli_arguments::ColorMetric;
+
+/// Backgrounds to blend semi-transparent colors onto if no background color is given. Comparing over both black and white
+/// tells transparency apart from any opaque color.
+const DEFAULT_BACKGROUND_COLORS: [[u8; 3]; 2] = [[0, 0, 0], [255, 255, 255]];
+
+/// OKLab distances are about a hundredth of the CIELAB based differences, so they are scaled up to keep weights like
+/// cost-weight comparable.
+const OKLAB_SCALE: f32 = 100.0;
+
+/// sRGB to CIE XYZ (D65) matrix.
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.4124564, 0.3575761, 0.1804375],
+    [0.2126729, 0.7151522, 0.072175],
+    [0.0193339, 0.119192, 0.9503041],
+];
+
+/// CIE XYZ to CAM16 cone response matrix.
+const XYZ_TO_CAM16_RGB: [[f32; 3]; 3] = [
+    [0.401288, 0.650173, -0.051461],
+    [-0.250268, 1.204414, 0.045854],
+    [-0.002079, 0.048952, 0.953127],
+];
+
+/// CAM16 viewing conditions of a typical sRGB display, a D65 white point at 64 lux with a 20% gray background and
+/// average surround.
+static VIEWING_CONDITIONS: Lazy<ViewingConditions> = Lazy::new(|| {
+    let white_point: [f32; 3] = [95.047, 100.0, 108.883];
+    let adapting_luminance = 64.0 / PI * 0.2;
+    let background_luminance: f32 = 20.0;
+    let (surround_factor, surround_exponent, chromatic_induction_factor): (f32, f32, f32) = (1.0, 0.69, 1.0);
+
+    let k = 1.0 / (5.0 * adapting_luminance + 1.0);
+    let luminance_adaptation_factor = 0.2 * k.powi(4) * 5.0 * adapting_luminance
+        + 0.1 * (1.0 - k.powi(4)).powi(2) * (5.0 * adapting_luminance).cbrt();
+    let background_ratio = background_luminance / white_point[1];
+    let brightness_induction_factor = 0.725 * background_ratio.powf(-0.2);
+    let degree_of_adaptation = (surround_factor * (1.0 - 1.0 / 3.6 * ((-adapting_luminance - 42.0) / 92.0).exp())).clamp(0.0, 1.0);
+
+    let white_rgb = multiply(XYZ_TO_CAM16_RGB, white_point);
+    let adaptation_factors = white_rgb.map(|channel| degree_of_adaptation * white_point[1] / channel + 1.0 - degree_of_adaptation);
+
+    let mut viewing_conditions = ViewingConditions {
+        adaptation_factors,
+        luminance_adaptation_factor,
+        brightness_induction_factor,
+        chromatic_induction_factor,
+        lightness_exponent: surround_exponent * (1.48 + background_ratio.sqrt()),
+        chroma_factor: (1.64 - 0.29f32.powf(background_ratio)).powf(0.73),
+        white_achromatic_response: 1.0,
+    };
+
+    viewing_conditions.white_achromatic_response = viewing_conditions.achromatic_response(viewing_conditions.adapted_rgb(white_point));
+
+    viewing_conditions
+});
+
+struct ViewingConditions {
+    /// Scales the cone responses to account for the eye adapting to the white point.
+    adaptation_factors: [f32; 3],
+    luminance_adaptation_factor: f32,
+    brightness_induction_factor: f32,
+    chromatic_induction_factor: f32,
+    lightness_exponent: f32,
+    chroma_factor: f32,
+    white_achromatic_response: f32,
+}
+
+impl ViewingConditions {
+    /// Post-adaptation cone responses of a CIE XYZ color.
+    fn adapted_rgb(&self, xyz: [f32; 3]) -> [f32; 3] {
+        let rgb = multiply(XYZ_TO_CAM16_RGB, xyz);
+
+        [0, 1, 2].map(|index| {
+            let response = (self.luminance_adaptation_factor * (self.adaptation_factors[index] * rgb[index]).abs() / 100.0).powf(0.42);
+
+            400.0 * (self.adaptation_factors[index] * rgb[index]).signum() * response / (response + 27.13)
+        })
+    }
+
+    fn achromatic_response(&self, adapted_rgb: [f32; 3]) -> f32 {
+        (2.0 * adapted_rgb[0] + adapted_rgb[1] + 0.05 * adapted_rgb[2]) * self.brightness_induction_factor
+    }
+}
+
+/// Perceptual difference between two colors that may be transparent, compared as they look blended onto the background
+/// color. Without one, the differences over black and over white are averaged. Lower is closer.
+///
+/// CIE94 and CMC weigh the difference by the chroma and hue of the reference color, so candidates compared against the
+/// same reference, e.g. a pixel of the image, are only measured on the same scale if it is always passed first.
+pub fn alpha_aware_color_difference(color_metric: ColorMetric, background_color: Option<[u8; 3]>, reference_rgba: Rgba<u8>, rgba: Rgba<u8>) -> f32 {
+    if reference_rgba[3] == 255 && rgba[3] == 255 {
+        return color_difference(color_metric, reference_rgba, rgba);
+    }
+
+    let background_colors = match &background_color {
+        Some(background_color) => slice::from_ref(background_color),
+        None => &DEFAULT_BACKGROUND_COLORS,
+    };
+
+    background_colors.iter()
+        .map(|&background_color| color_difference(color_metric, blend(reference_rgba, background_color), blend(rgba, background_color)))
+        .sum::<f32>() / background_colors.len() as f32
+}
+
+/// Perceptual difference between two colors, ignoring alpha. Lower is closer.
+fn color_difference(color_metric: ColorMetric, reference_rgba: Rgba<u8>, rgba: Rgba<u8>) -> f32 {
+    match color_metric {
+        ColorMetric::Cie76 => delta_e::DE1976::new(lab(reference_rgba), lab(rgba)),
+        ColorMetric::Cie94 => delta_e::DE1994::new(lab(reference_rgba), lab(rgba)),
+        ColorMetric::Cmc => delta_e::DECMC::new(lab(reference_rgba), lab(rgba), 1.0, 1.0),
+        ColorMetric::De2000 => delta_e::DE2000::new(lab(reference_rgba), lab(rgba)),
+        ColorMetric::Oklab => euclidean_distance(oklab(reference_rgba), oklab(rgba)) * OKLAB_SCALE,
+        ColorMetric::Cam16Ucs => euclidean_distance(cam16_ucs(reference_rgba), cam16_ucs(rgba)),
+    }
+}
+
+/// Opaque color of a transparent color drawn over the background color.
+fn blend(rgba: Rgba<u8>, background_color: [u8; 3]) -> Rgba<u8> {
+    let alpha = rgba[3] as u16;
+    let [r, g, b] = [0, 1, 2].map(|index| ((rgba[index] as u16 * alpha + background_color[index] as u16 * (255 - alpha) + 127) / 255) as u8);
+
+    Rgba([r, g, b, 255])
+}
+
+fn lab(rgba: Rgba<u8>) -> Lab {
+    *lab::rgb_bytes_to_labs(&rgba.0[0..3]).first().unwrap()
+}
+
+/// OKLab color as described by Björn Ottosson, see https://bottosson.github.io/posts/oklab/.
+fn oklab(rgba: Rgba<u8>) -> [f32; 3] {
+    let [r, g, b] = linear_rgb(rgba);
+
+    let l = (0.41222147 * r + 0.53633254 * g + 0.051445993 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806995 * g + 0.10739696 * b).cbrt();
+    let s = (0.08830246 * r + 0.28171884 * g + 0.6299787 * b).cbrt();
+
+    [
+        0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904037 * l + 0.78277177 * m - 0.80867577 * s,
+    ]
+}
+
+/// Color in the uniform color space CAM16-UCS (J', a', b') under `VIEWING_CONDITIONS`.
+fn cam16_ucs(rgba: Rgba<u8>) -> [f32; 3] {
+    let viewing_conditions = &*VIEWING_CONDITIONS;
+
+    let xyz = multiply(SRGB_TO_XYZ, linear_rgb(rgba)).map(|channel| channel * 100.0);
+    let adapted_rgb = viewing_conditions.adapted_rgb(xyz);
+
+    let a = adapted_rgb[0] - 12.0 * adapted_rgb[1] / 11.0 + adapted_rgb[2] / 11.0;
+    let b = (adapted_rgb[0] + adapted_rgb[1] - 2.0 * adapted_rgb[2]) / 9.0;
+    let hue = b.atan2(a);
+
+    let lightness = 100.0 * (viewing_conditions.achromatic_response(adapted_rgb) / viewing_conditions.white_achromatic_response)
+        .powf(viewing_conditions.lightness_exponent);
+
+    let eccentricity = 0.25 * ((hue + 2.0).cos() + 3.8);
+    let t = 50000.0 / 13.0 * viewing_conditions.chromatic_induction_factor * viewing_conditions.brightness_induction_factor
+        * eccentricity * a.hypot(b) / (adapted_rgb[0] + adapted_rgb[1] + 1.05 * adapted_rgb[2] + 0.305);
+    let chroma = t.powf(0.9) * viewing_conditions.chroma_factor * (lightness / 100.0).sqrt();
+    let colorfulness = chroma * viewing_conditions.luminance_adaptation_factor.powf(0.25);
+
+    let ucs_lightness = 1.7 * lightness / (1.0 + 0.007 * lightness);
+    let ucs_colorfulness = (1.0 + 0.0228 * colorfulness).ln() / 0.0228;
+
+    [ucs_lightness, ucs_colorfulness * hue.cos(), ucs_colorfulness * hue.sin()]
+}
+
+/// sRGB channels without gamma, from 0 to 1.
+fn linear_rgb(rgba: Rgba<u8>) -> [f32; 3] {
+    [rgba[0], rgba[1], rgba[2]].map(|channel| {
+        let channel = channel as f32 / 255.0;
+
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+fn multiply(matrix: [[f32; 3]; 3], vector: [f32; 3]) -> [f32; 3] {
+    matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+fn euclidean_distance(color_1: [f32; 3], color_2: [f32; 3]) -> f32 {
+    ((color_1[0] - color_2[0]).powi(2) + (color_1[1] - color_2[1]).powi(2) + (color_1[2] - color_2[2]).powi(2)).sqrt()
+}
\ No newline at end of file
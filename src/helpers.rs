@@ -1,3 +1,13 @@
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+
+use camino::Utf8Path;
+use color_eyre::eyre;
+use color_eyre::eyre::{eyre, WrapErr};
+use flate2::Compression;
+use flate2::write::GzEncoder;
 use image::{DynamicImage, GenericImage, Rgba};
 
 pub trait FillPixels {
@@ -12,4 +22,44 @@ impl FillPixels for DynamicImage {
             }
         }
     }
+}
+
+pub fn gzip(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+/// fastnbt always writes an unnamed root compound, some formats require it to carry a name.
+pub fn with_nbt_root_name(nbt_bytes: Vec<u8>, root_name: &str) -> Vec<u8> {
+    let mut named_nbt_bytes = Vec::with_capacity(nbt_bytes.len() + root_name.len());
+
+    named_nbt_bytes.push(nbt_bytes[0]);
+    named_nbt_bytes.extend((root_name.len() as u16).to_be_bytes());
+    named_nbt_bytes.extend(root_name.as_bytes());
+    named_nbt_bytes.extend(&nbt_bytes[3..]);
+
+    named_nbt_bytes
+}
+
+/// Block ID with the `minecraft:` namespace Minecraft assumes when none is written.
+pub fn namespaced_block_id(block_id: &str) -> String {
+    if block_id.contains(':') {
+        block_id.to_string()
+    } else {
+        format!("minecraft:{block_id}")
+    }
+}
+
+/// Reads a user file with one `<block id>|<value>` line per block, skipping empty lines and `#` comments.
+pub fn read_block_values<T: FromStr>(path: &Utf8Path, value_name: &str) -> eyre::Result<Vec<(String, T)>> {
+    let file_contents = fs::read_to_string(path).wrap_err_with(|| format!("Unable to read '{path}'."))?;
+
+    file_contents.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_once('|')
+            .and_then(|(block_id, value)| Some((namespaced_block_id(block_id.trim()), value.trim().parse::<T>().ok()?)))
+            .ok_or_else(|| eyre!("Invalid line '{line}' in '{path}', expected <BLOCK ID>|<{value_name}>.")))
+        .collect()
 }
\ No newline at end of file
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use fastnbt::ByteArray;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+use crate::helpers;
+
+/// Pre-1.13 numeric block IDs by modern block ID (or full block state, which takes precedence), see `legacy_block_ids.txt`.
+static LEGACY_BLOCK_IDS: Lazy<HashMap<&'static str, LegacyBlockId>> = Lazy::new(|| {
+    include_str!("legacy_block_ids.txt").lines()
+        .map(|line| match line.split('|').collect::<Vec<_>>()[..] {
+            [block_state, id, data] => (block_state, LegacyBlockId { id: id.parse().unwrap(), data: data.parse().unwrap(), data_mapping: None }),
+            [block_state, id, data, data_mapping] => (block_state, LegacyBlockId { id: id.parse().unwrap(), data: data.parse().unwrap(), data_mapping: Some(data_mapping) }),
+            _ => panic!("Invalid legacy block ID line '{}'.", line)
+        })
+        .collect()
+});
+
+struct LegacyBlockId {
+    id: u8,
+    data: u8,
+    data_mapping: Option<&'static str>,
+}
+
+pub fn make_bytes(
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<Vec<u8>> {
+    // Map of texture name to legacy block ID and data value
+    let legacy_blocks_by_texture = block_volume.texture_names()
+        .map(|texture_name| Ok((texture_name, to_legacy_block(&block_textures_and_states[texture_name])?)))
+        .collect::<eyre::Result<HashMap<_, _>>>()?;
+
+    // Blocks are ordered by Y, then Z, then X
+    let (blocks, data): (Vec<_>, Vec<_>) = block_volume.texture_names()
+        .map(|texture_name| {
+            let (block_id, block_data) = legacy_blocks_by_texture[texture_name];
+
+            (block_id as i8, block_data as i8)
+        })
+        .unzip();
+
+    Ok(helpers::gzip(&helpers::with_nbt_root_name(
+        fastnbt::to_bytes(&Schematic {
+            width: block_volume.size[0] as u16 as i16,
+            height: block_volume.size[1] as u16 as i16,
+            length: block_volume.size[2] as u16 as i16,
+            materials: "Alpha".into(),
+            blocks: ByteArray::new(blocks),
+            data: ByteArray::new(data),
+            entities: vec![],
+            tile_entities: vec![],
+        })?,
+        "Schematic"
+    ))?)
+}
+
+fn to_legacy_block(block: &TextureWithBlockState) -> eyre::Result<(u8, u8)> {
+    let block_state = block.block_state_string();
+
+    let no_legacy_id_error = || eyre!("Block '{}' has no legacy block ID and cannot be written to a .schematic file. Exclude it from the palette or use another output format.", block_state);
+
+    if let Some(legacy_block_id) = LEGACY_BLOCK_IDS.get(block_state.as_str()) {
+        return Ok((legacy_block_id.id, legacy_block_id.data));
+    }
+
+    let legacy_block_id = LEGACY_BLOCK_IDS.get(block.block_id.as_str()).ok_or_else(no_legacy_id_error)?;
+
+    let property = |name: &str, default: &'static str| block.block_state_properties.as_ref()
+        .and_then(|block_state_properties| block_state_properties.get(name))
+        .map(|value| value.as_str())
+        .unwrap_or(default);
+
+    // Data values as assigned by the 1.13 world upgrade
+    let data_offset = match legacy_block_id.data_mapping {
+        None => 0,
+        Some("axis") => match property("axis", "y") {
+            "y" => 0,
+            "x" => 4,
+            _ => 8,
+        },
+        Some("quartz_axis") => match property("axis", "y") {
+            "y" => 0,
+            "x" => 1,
+            _ => 2,
+        },
+        Some("facing") => match property("facing", "north") {
+            "down" => 0,
+            "up" => 1,
+            "north" => 2,
+            "south" => 3,
+            "west" => 4,
+            _ => 5,
+        },
+        Some("furnace_facing") => match property("facing", "north") {
+            "north" => 2,
+            "south" => 3,
+            "west" => 4,
+            _ => 5,
+        },
+        Some("horizontal_facing") => match property("facing", "north") {
+            "south" => 0,
+            "west" => 1,
+            "north" => 2,
+            _ => 3,
+        },
+        Some("stairs") => {
+            let facing = match property("facing", "north") {
+                "east" => 0,
+                "west" => 1,
+                "south" => 2,
+                _ => 3,
+            };
+
+            facing + if property("half", "bottom") == "top" { 4 } else { 0 }
+        }
+        Some("slab") => match property("type", "bottom") {
+            "bottom" => 0,
+            "top" => 8,
+            _ => return Err(no_legacy_id_error()),
+        },
+        Some("trapdoor") => {
+            let facing = match property("facing", "north") {
+                "north" => 0,
+                "south" => 1,
+                "west" => 2,
+                _ => 3,
+            };
+
+            facing + if property("open", "false") == "true" { 4 } else { 0 } + if property("half", "bottom") == "top" { 8 } else { 0 }
+        }
+        Some("mushroom") => {
+            let sides = ["up", "down", "north", "south", "east", "west"].map(|side| property(side, "true"));
+
+            if sides.iter().all(|&side| side == "true") {
+                14
+            } else if sides.iter().all(|&side| side == "false") {
+                0
+            } else {
+                return Err(no_legacy_id_error());
+            }
+        }
+        Some(data_mapping) => panic!("Unknown legacy data mapping '{}'.", data_mapping)
+    };
+
+    Ok((legacy_block_id.id, legacy_block_id.data + data_offset))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Schematic {
+    width: i16,
+    height: i16,
+    length: i16,
+    materials: String,
+    blocks: ByteArray,
+    data: ByteArray,
+    entities: Vec<EmptyObject>,
+    tile_entities: Vec<EmptyObject>,
+}
+
+#[derive(Serialize)]
+struct EmptyObject;
\ No newline at end of file
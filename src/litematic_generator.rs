@@ -1,26 +1,29 @@
 use std::collections::HashMap;
 use std::time;
 use std::time::SystemTime;
+use camino::Utf8Path;
 use color_eyre::eyre;
 use fastnbt::LongArray;
 use itertools::Itertools;
 use serde::Serialize;
+use crate::block_state_packing;
+use crate::block_volume::BlockVolume;
 use crate::blocks::TextureWithBlockState;
-use crate::cli_arguments::CliArguments;
 
 pub fn make_bytes(
-    block_width: usize,
-    cli_arguments: &CliArguments,
-    output_blocks: &Vec<Vec<String>>,
+    output_path: &Utf8Path,
+    data_version: i32,
+    tint_biome: Option<&str>,
+    block_volume: &BlockVolume,
     block_textures_and_states: &HashMap<String, TextureWithBlockState>
 ) -> eyre::Result<Vec<u8>> {
-    let block_count = block_width * cli_arguments.block_height;
+    let block_count = block_volume.size.iter().product::<usize>();
 
-    let air_block_list = vec!["air".to_string()];
+    let air_block_list = ["air".to_string()];
 
     // Unique list of all used textures as texture names
     let used_block_textures = air_block_list.iter()
-        .chain(output_blocks.iter().flat_map(|column| column))
+        .chain(block_volume.texture_names())
         .unique()
         .collect::<Vec<_>>();
 
@@ -39,23 +42,22 @@ pub fn make_bytes(
         .collect::<Vec<_>>();
 
     Ok(fastnbt::to_bytes(&Schematic {
-        minecraft_data_version: 3465,
+        minecraft_data_version: data_version,
         sub_version: 1,
         version: 6,
         metadata: Metadata {
-            enclosing_size: XYZ {
-                x: block_width as i32,
-                y: cli_arguments.block_height as i32,
-                z: 1,
-            },
+            enclosing_size: XYZ::from(block_volume.size),
             region_count: 1,
-            total_blocks: (block_count - output_blocks.iter().flat_map(|column| column).filter(|&block| block == "air").count()) as i32,
+            total_blocks: (block_count - block_volume.texture_names().filter(|&block| block == "air").count()) as i32,
             total_volume: block_count as i32,
             time_created: SystemTime::now().duration_since(time::UNIX_EPOCH)?.as_millis() as i64,
             time_modified: SystemTime::now().duration_since(time::UNIX_EPOCH)?.as_millis() as i64,
             author: "img2mc".into(),
-            description: "Generated by img2mc".into(),
-            name: cli_arguments.output_path.file_stem().unwrap_or("image").into(),
+            description: match tint_biome {
+                Some(tint_biome) => format!("Generated by img2mc, colored for the {tint_biome} biome"),
+                None => "Generated by img2mc".into(),
+            },
+            name: output_path.file_stem().unwrap_or("image").into(),
         },
         regions: HashMap::from([
             ("Unnamed".into(), Region {
@@ -64,11 +66,7 @@ pub fn make_bytes(
                     y: 0,
                     z: 0,
                 },
-                size: XYZ {
-                    x: block_width as i32,
-                    y: cli_arguments.block_height as i32,
-                    z: 1,
-                },
+                size: XYZ::from(block_volume.size),
                 block_state_palette: block_states_info.into_iter()
                     .map(|(block_id, block_state_properties)| {
                         BlockStatePaletteEntry {
@@ -82,29 +80,14 @@ pub fn make_bytes(
                 pending_fluid_ticks: vec![],
                 tile_entities: vec![],
                 block_states: {
-                    let bits_per_block = ((used_block_textures.len() as f32).log2().ceil() as usize).max(2);
-
-                    let mut longs = vec![0i64; bits_per_block * block_width * cli_arguments.block_height / 64 + 1];
-
-                    let mut bit_index = 0;
-
-                    for y in (0..cli_arguments.block_height).rev() {
-                        for x in 0..block_width {
-                            longs[bit_index / 64] |= (block_state_palette_by_texture[&output_blocks[x][y]] as i64) << bit_index % 64;
-
-                            if bit_index % 64 + bits_per_block >= 64 {
-                                let written_bits = 64 - bit_index % 64;
-
-                                bit_index += written_bits;
-                                longs[bit_index / 64] |= (block_state_palette_by_texture[&output_blocks[x][y]] as i64) >> written_bits;
-                                bit_index += bits_per_block - written_bits;
-                            } else {
-                                bit_index += bits_per_block;
-                            }
-                        }
-                    }
-
-                    LongArray::new(longs)
+                    let palette_indices = block_volume.texture_names()
+                        .map(|texture_name| block_state_palette_by_texture[texture_name])
+                        .collect::<Vec<_>>();
+
+                    LongArray::new(block_state_packing::pack_spanning(
+                        &palette_indices,
+                        block_state_packing::bits_per_block(used_block_textures.len(), 2)
+                    ))
                 }
             })
         ]),
@@ -149,12 +132,23 @@ struct Region {
 }
 
 #[derive(Serialize)]
+#[allow(clippy::upper_case_acronyms)]
 struct XYZ {
     x: i32,
     y: i32,
     z: i32
 }
 
+impl From<[usize; 3]> for XYZ {
+    fn from([x, y, z]: [usize; 3]) -> Self {
+        Self {
+            x: x as i32,
+            y: y as i32,
+            z: z as i32,
+        }
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "PascalCase")]
 struct BlockStatePaletteEntry {
@@ -1,5 +1,6 @@
 use std::{fs, io, thread};
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::io::Write;
 use std::sync::{Arc, atomic};
 use std::sync::atomic::AtomicUsize;
@@ -10,18 +11,36 @@ use color_eyre::eyre::eyre;
 use gumdrop::Options;
 use image::{DynamicImage, GenericImage, GenericImageView, Pixel, Rgba, RgbaImage};
 use image::imageops::FilterType;
-use lab::Lab;
 use rayon::iter::ParallelIterator;
 use rayon::prelude::IntoParallelRefIterator;
 
 use crate::block_texture_chunk_extractor::BlockTextureData;
+use crate::block_volume::BlockVolume;
 use crate::cli_arguments::CliArguments;
+use crate::material_budget::MaterialBudget;
 
 mod blocks;
+pub mod anvil_world_writer;
+pub mod biomes;
+pub mod block_metadata;
+pub mod block_physics;
+pub mod block_state_packing;
 pub mod block_texture_chunk_extractor;
+pub mod block_volume;
 pub mod cli_arguments;
+pub mod color_metric;
 pub mod helpers;
+pub mod legacy_schematic_generator;
 pub mod litematic_generator;
+pub mod map_art;
+pub mod material_budget;
+pub mod mcfunction_generator;
+pub mod mcstructure_generator;
+pub mod minecraft_versions;
+pub mod orientation;
+pub mod palette_presets;
+pub mod sponge_schematic_generator;
+pub mod structure_generator;
 
 
 fn main() -> eyre::Result<()> {
@@ -30,12 +49,47 @@ fn main() -> eyre::Result<()> {
     let subscriber = tracing_subscriber::FmtSubscriber::new();
     tracing::subscriber::set_global_default(subscriber)?;
 
-    let cli_arguments = CliArguments::parse_args_default_or_exit();
+    let mut cli_arguments = CliArguments::parse_args_default_or_exit();
 
+    // Every block shows up as a single pixel on a map
+    if cli_arguments.map_art.is_some() {
+        cli_arguments.chunk_resolution = 1;
+    }
+
+    if cli_arguments.output_path.is_none() && cli_arguments.world_path.is_none() {
+        return Err(eyre!("Either an output path or a world to place the result in is required."));
+    }
+
+    if cli_arguments.world_path.is_some() && !cli_arguments.minecraft_version.has_modern_chunk_format() {
+        return Err(eyre!("Placing the result into a world requires Minecraft 1.18 or newer."));
+    }
 
-    let BlockTextureData { block_textures_and_states, chunk_average_color_map } = block_texture_chunk_extractor::extract(&cli_arguments)?;
+    if cli_arguments.cost_weight < 0.0 {
+        return Err(eyre!("The cost weight cannot be negative."));
+    }
+
+
+    let BlockTextureData { block_textures_and_states, chunk_average_color_map, texture_resolution } = block_texture_chunk_extractor::extract(&cli_arguments)?;
     tracing::info!("Loaded {} texture(s) into {} chunks.", chunk_average_color_map.len(), chunk_average_color_map.len() * cli_arguments.chunk_resolution * cli_arguments.chunk_resolution);
 
+    let block_costs = block_metadata::block_costs(
+        block_textures_and_states.values().map(|block| block.block_id.as_str()),
+        cli_arguments.block_costs_path.as_deref()
+    )?;
+
+    // Air is never bought, so empty blocks cost nothing both when selecting blocks and in the cost summary
+    let cost_by_texture = chunk_average_color_map.keys()
+        .map(|texture_name| (texture_name.as_str(), match block_textures_and_states[texture_name].block_id.as_str() {
+            "minecraft:air" => 0.0,
+            block_id => block_costs[block_id],
+        }))
+        .collect::<HashMap<_, _>>();
+
+    let mut material_budget = cli_arguments.material_budget_path.as_deref().map(MaterialBudget::load).transpose()?;
+
+    // The weight is given per chunk while texture errors are summed over all chunks of a block
+    let block_cost_weight = cli_arguments.cost_weight * (cli_arguments.chunk_resolution * cli_arguments.chunk_resolution) as f32;
+
     let (source_image, block_width) = get_source_image(&cli_arguments)?;
 
 
@@ -58,7 +112,22 @@ fn main() -> eyre::Result<()> {
         .ok_or(eyre!("Invalid dithering matrix"))?
         .0;
 
-    let dithering_total_weight: usize = dithering_matrix.iter().flat_map(|n| n).sum();
+    let dithering_total_weight: usize = dithering_matrix.iter().flatten().sum();
+
+    let background_color = cli_arguments.background_color.as_ref().map(|background_color| background_color.0);
+
+    // Totals of the closest matching blocks and the selected ones, to report what preferring cheaper blocks changed
+    let mut closest_blocks_cost = 0.0;
+    let mut selected_blocks_cost = 0.0;
+    let mut added_error = 0.0;
+
+    let mut substituted_block_count = 0;
+    let mut emptied_block_count = 0;
+
+    let supports_unstable_blocks = block_physics::plans_support(&cli_arguments);
+
+    // What the block last placed in each column needs below it to not fall or break
+    let mut column_support = vec![None; block_width];
 
 
     tracing::info!("Processing chunks...");
@@ -89,40 +158,22 @@ fn main() -> eyre::Result<()> {
                     // Calculate the error between every chunk in the source image and the block texture
                     let mut texture_error = 0.0;
 
-                    let mut transparent_pixels_present = false;
-
-                    // Check if transparent pixels are present to decide which error algorithm should be used later
-                    'outer: for x_within_chunk in 0..cli_arguments.chunk_resolution {
-                        for y_within_chunk in 0..cli_arguments.chunk_resolution {
-                            let pixel_rgba_data = source_image.get_pixel(
-                                (chunk_x * cli_arguments.chunk_resolution + x_within_chunk) as u32,
-                                (chunk_y * cli_arguments.chunk_resolution + y_within_chunk) as u32
-                            );
-
-                            if pixel_rgba_data[3] != 255 || texture_color_map[x_within_chunk][y_within_chunk][3] != 255 {
-                                transparent_pixels_present = true;
-                                break 'outer;
-                            }
-                        }
-                    }
-
+                    #[allow(clippy::needless_range_loop)]
                     for x_within_chunk in 0..cli_arguments.chunk_resolution {
                         for y_within_chunk in 0..cli_arguments.chunk_resolution {
                             // Add residential quantization error to the current chunk
                             let pixel_rgba_data = source_image.get_pixel(
                                 (chunk_x * cli_arguments.chunk_resolution + x_within_chunk) as u32,
                                 (chunk_y * cli_arguments.chunk_resolution + y_within_chunk) as u32
-                            ).map_with_index(|channel, index| (channel as isize + error_values[chunk_x][chunk_y][index]).min(255).max(0) as u8);
+                            ).map_with_index(|channel, index| (channel as isize + error_values[chunk_x][chunk_y][index]).clamp(0, 255) as u8);
 
                             // Calculate how close the chunk of the current texture is to the source image
-                            texture_error += if !transparent_pixels_present {
-                                delta_e::DE2000::new(
-                                    texture_color_map[x_within_chunk][y_within_chunk].to_lab(),
-                                    pixel_rgba_data.to_lab()
-                                )
-                            } else {
-                                euclidean_distance(texture_color_map[x_within_chunk][y_within_chunk], pixel_rgba_data) as f32
-                            };
+                            texture_error += color_metric::alpha_aware_color_difference(
+                                cli_arguments.color_metric,
+                                background_color,
+                                pixel_rgba_data,
+                                texture_color_map[x_within_chunk][y_within_chunk]
+                            );
                         }
                     }
 
@@ -133,15 +184,70 @@ fn main() -> eyre::Result<()> {
             // Select texture with lowest error
             error_by_texture.sort_by(|(_, error_1), (_, error_2)| error_1.partial_cmp(error_2).unwrap_or(Ordering::Equal));
 
-            let (lowest_error_texture, _) = error_by_texture[0];
-            output_blocks[chunk_x][chunk_y] = lowest_error_texture.clone();
+            // Rows are placed from the top, so blocks placed below unstable ones have to hold them. The bottom row rests on
+            // the ground.
+            if let Some(support) = column_support[chunk_x] {
+                error_by_texture.retain(|(texture_name, _)| block_physics::can_support(&block_textures_and_states[*texture_name], support));
+            }
+
+            let Some(&(closest_texture, _)) = error_by_texture.first() else {
+                return Err(eyre!("No block of the palette can hold the unstable block above {}, {}.", chunk_x, chunk_y));
+            };
+
+            // Blocks that ran out are replaced by the next best ones, the dithering makes up for the difference
+            if let Some(material_budget) = &material_budget {
+                error_by_texture.retain(|(texture_name, _)| material_budget.is_available(&block_textures_and_states[*texture_name].block_id));
+            }
+
+            let Some(&(lowest_error_texture, lowest_error)) = error_by_texture.first() else {
+                return Err(eyre!(
+                    "The material budget ran out after {} of {} blocks.",
+                    chunk_y * block_width + chunk_x,
+                    block_width * cli_arguments.block_height
+                ));
+            };
+
+            if lowest_error_texture != closest_texture {
+                substituted_block_count += 1;
+
+                // Air is never part of the budget, so it takes over once nothing else is left
+                if lowest_error_texture == "air" {
+                    emptied_block_count += 1;
+                }
+            }
 
-            if lowest_error_texture == "" {
-                panic!("asdf {}", lowest_error_texture);
+            // Trade color accuracy for cheaper blocks, equally good blocks keep the closest match
+            let (selected_texture, selected_error) = if cli_arguments.cost_weight > 0.0 {
+                error_by_texture.iter()
+                    .copied()
+                    .min_by(|(texture_name_1, error_1), (texture_name_2, error_2)| {
+                        (error_1 + block_cost_weight * cost_by_texture[texture_name_1.as_str()])
+                            .total_cmp(&(error_2 + block_cost_weight * cost_by_texture[texture_name_2.as_str()]))
+                    })
+                    .unwrap_or((lowest_error_texture, lowest_error))
+            } else {
+                (lowest_error_texture, lowest_error)
+            };
+
+            closest_blocks_cost += cost_by_texture[lowest_error_texture.as_str()];
+            selected_blocks_cost += cost_by_texture[selected_texture.as_str()];
+            added_error += selected_error - lowest_error;
+
+            if let Some(material_budget) = &mut material_budget {
+                material_budget.take(&block_textures_and_states[selected_texture].block_id);
+            }
+
+            column_support[chunk_x] = block_physics::required_support(&block_textures_and_states[selected_texture].block_id)
+                .filter(|_| supports_unstable_blocks);
+
+            output_blocks[chunk_x][chunk_y] = selected_texture.clone();
+
+            if selected_texture.is_empty() {
+                panic!("asdf {}", selected_texture);
             }
 
             // Calculating residual quantization error
-            let rgba_by_coords_in_block_texture = chunk_average_color_map[lowest_error_texture].iter()
+            let rgba_by_coords_in_block_texture = chunk_average_color_map[selected_texture].iter()
                 .enumerate()
                 .flat_map(|(x, row)| row.iter()
                     .enumerate()
@@ -166,6 +272,7 @@ fn main() -> eyre::Result<()> {
             let residual_quantization_error = residual_quantization_error.map(|channel| channel / (cli_arguments.chunk_resolution * cli_arguments.chunk_resolution) as isize);
 
 
+            #[allow(clippy::needless_range_loop)]
             for chunk_y_offset in 0..dithering_matrix.len() {
                 for chunk_x_offset in 0..dithering_matrix[0].len() {
                     let dithering_chunk_x = chunk_x as isize + chunk_x_offset as isize - dithering_center_x as isize;
@@ -188,25 +295,82 @@ fn main() -> eyre::Result<()> {
     print!("{: <80}\r", "\r");
     io::stdout().flush()?;
 
+    if substituted_block_count > 0 {
+        tracing::info!("Used the next best block for {} block(s) whose material ran out or is not part of the budget.", substituted_block_count);
+    }
 
-    match cli_arguments.output_path.extension().ok_or(eyre!("Output path does not have a file extension."))? {
-        "litematic" | "schematic" => {
-            fs::write(&cli_arguments.output_path, litematic_generator::make_bytes(block_width, &cli_arguments, &output_blocks, &block_textures_and_states)?)?;
-        }
-        _ => {
-            let mut output_image = RgbaImage::new((block_width * 16) as u32, (cli_arguments.block_height * 16) as u32);
+    if emptied_block_count > 0 {
+        tracing::warn!("Left {} block(s) empty because the material budget had nothing closer than air.", emptied_block_count);
+    }
+
+    if cli_arguments.cost_weight > 0.0 {
+        tracing::info!(
+            "Preferring cheaper blocks lowered the material cost from {:.0} to {:.0} ({:.1}% saved) while adding a color error of {:.2} per chunk on average.",
+            closest_blocks_cost,
+            selected_blocks_cost,
+            if closest_blocks_cost > 0.0 { (closest_blocks_cost - selected_blocks_cost) / closest_blocks_cost * 100.0 } else { 0.0 },
+            added_error / (block_width * cli_arguments.block_height * cli_arguments.chunk_resolution * cli_arguments.chunk_resolution) as f32
+        );
+    }
 
-            for x in 0..block_width {
-                for y in 0..cli_arguments.block_height {
-                    output_image.copy_from(&block_textures_and_states[&output_blocks[x][y]].texture, (x * 16) as u32, (y * 16) as u32)?;
+
+    let block_volume = match cli_arguments.map_art {
+        Some(_) => map_art::floor_volume(&output_blocks, &block_textures_and_states, &cli_arguments.minecraft_version)?,
+        None => cli_arguments.orientation.reorient_volume(&BlockVolume::wall(&output_blocks)),
+    };
+
+
+    let tint_biome = biomes::tint_biome_name(&cli_arguments.biome, &block_volume, &block_textures_and_states);
+
+    if let Some(tint_biome) = tint_biome {
+        tracing::info!("Grass, leaves and water were colored for the '{}' biome and only look right there.", tint_biome);
+    }
+
+    if let Some(output_path) = &cli_arguments.output_path {
+        match output_path.extension().ok_or(eyre!("Output path does not have a file extension."))? {
+            "litematic" => {
+                fs::write(output_path, litematic_generator::make_bytes(output_path, cli_arguments.minecraft_version.data_version, tint_biome, &block_volume, &block_textures_and_states)?)?;
+            }
+            "schematic" => {
+                fs::write(output_path, legacy_schematic_generator::make_bytes(&block_volume, &block_textures_and_states)?)?;
+            }
+            "schem" => {
+                fs::write(output_path, sponge_schematic_generator::make_bytes(output_path, &cli_arguments, tint_biome, &block_volume, &block_textures_and_states)?)?;
+            }
+            "nbt" => {
+                for (path, bytes) in structure_generator::make_files(output_path, cli_arguments.minecraft_version.data_version, &block_volume, &block_textures_and_states)? {
+                    fs::write(path, bytes)?;
                 }
             }
+            "mcstructure" => {
+                fs::write(output_path, mcstructure_generator::make_bytes(&block_volume, &block_textures_and_states)?)?;
+            }
+            "mcfunction" | "zip" => {
+                for (path, bytes) in mcfunction_generator::make_files(output_path, &cli_arguments, tint_biome, &block_volume, &block_textures_and_states)? {
+                    fs::write(path, bytes)?;
+                }
+            }
+            _ => {
+                let mut output_image = RgbaImage::new(block_width as u32 * texture_resolution, cli_arguments.block_height as u32 * texture_resolution);
 
-            output_image.save(&cli_arguments.output_path)?;
+                for x in 0..block_width {
+                    for y in 0..cli_arguments.block_height {
+                        output_image.copy_from(&block_textures_and_states[&output_blocks[x][y]].texture, x as u32 * texture_resolution, y as u32 * texture_resolution)?;
+                    }
+                }
+
+                output_image.save(output_path)?;
+            }
         }
+
+        tracing::info!("Saved result to '{}'.", output_path);
     }
 
-    tracing::info!("Saved result to '{}'.", cli_arguments.output_path);
+    if let Some(world_path) = &cli_arguments.world_path {
+        anvil_world_writer::place(world_path, &cli_arguments, &block_volume, &block_textures_and_states)?;
+
+        tracing::info!("Placed result in world '{}' at {:?}.", world_path, cli_arguments.world_origin.0);
+    }
 
 
     Ok(())
@@ -235,15 +399,6 @@ fn get_source_image(cli_arguments: &CliArguments) -> eyre::Result<(DynamicImage,
     Ok((source_image, block_width))
 }
 
-fn euclidean_distance(rgba_1: Rgba<u8>, rgba_2: Rgba<u8>) -> usize {
-    (
-        (rgba_1[0] as isize - rgba_2[0] as isize).pow(2)
-        + (rgba_1[1] as isize - rgba_2[1] as isize).pow(2)
-        + (rgba_1[2] as isize - rgba_2[2] as isize).pow(2)
-        + ((rgba_1[3] as isize - rgba_2[3] as isize) * 1).pow(2)
-    ) as usize
-}
-
 trait MapWithIndex<T> {
     fn map_with_index<F: Fn(T, usize) -> U, U>(&self, f: F) -> Rgba<U>;
 }
@@ -257,14 +412,4 @@ impl <T: Copy> MapWithIndex<T> for Rgba<T> {
             f(self.0[3], 3),
         ])
     }
-}
-
-trait ToLab {
-    fn to_lab(&self) -> Lab;
-}
-
-impl ToLab for Rgba<u8> {
-    fn to_lab(&self) -> Lab {
-        *lab::rgb_bytes_to_labs(&self.0[0..3]).first().unwrap()
-    }
 }
\ No newline at end of file
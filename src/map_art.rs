@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use image::{Rgba, RgbaImage};
+use once_cell::sync::Lazy;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+use crate::cli_arguments::TextureFilteringMode;
+use crate::minecraft_versions::MinecraftVersion;
+
+/// Map base colors with the block used to produce each of them, see `map_colors.txt`. Colors that can only be produced by
+/// fluids or unobtainable blocks are left out.
+static MAP_COLORS: Lazy<Vec<MapColor>> = Lazy::new(|| {
+    include_str!("map_colors.txt").lines()
+        .map(|line| {
+            let (id, rgb, block_id, block_state_properties) = match line.split('|').collect::<Vec<_>>()[..] {
+                [id, rgb, block_id] => (id, rgb, block_id, None),
+                [id, rgb, block_id, block_state_properties] => (id, rgb, block_id, Some(block_state_properties)),
+                _ => panic!("Invalid map color line '{}'.", line)
+            };
+
+            let rgb = match rgb.split(',').map(|channel| channel.parse::<u8>()).collect::<Result<Vec<_>, _>>() {
+                Ok(channels) if channels.len() == 3 => [channels[0], channels[1], channels[2]],
+                _ => panic!("Invalid map color '{}'.", rgb)
+            };
+
+            MapColor {
+                id: id.parse().unwrap(),
+                rgb,
+                block_id,
+                block_state_properties: block_state_properties.map(|block_state_properties| {
+                    block_state_properties.split(',')
+                        .map(|property_definition| match property_definition.split('=').collect::<Vec<_>>()[..] {
+                            [name, value] => (name.to_string(), value.to_string()),
+                            _ => panic!("Invalid property definition '{}'.", property_definition)
+                        })
+                        .collect()
+                }),
+            }
+        })
+        .collect()
+});
+
+/// Brightness multipliers (out of 255) of the four map color shades, indexed by shade.
+const SHADE_MULTIPLIERS: [u32; 4] = [180, 220, 255, 135];
+
+/// Shade of blocks lower than the block north of them.
+const DARKER_SHADE: usize = 0;
+
+/// Shade of blocks at the same height as the block north of them.
+pub const FLAT_SHADE: usize = 1;
+
+/// Shade of blocks higher than the block north of them.
+const LIGHTER_SHADE: usize = 2;
+
+/// Shades that can be produced by placing blocks at different heights, the darkest shade only occurs in the game's own maps.
+pub const STAIRCASED_SHADES: [usize; 3] = [DARKER_SHADE, FLAT_SHADE, LIGHTER_SHADE];
+
+/// Block placed under carpets, which would otherwise break as soon as they receive a block update.
+const SUPPORT_BLOCK_ID: &str = "minecraft:cobblestone";
+
+pub const SUPPORT_TEXTURE_NAME: &str = "map_art_support";
+
+struct MapColor {
+    id: u8,
+    rgb: [u8; 3],
+    block_id: &'static str,
+    block_state_properties: Option<HashMap<String, String>>,
+}
+
+/// Creates a solid texture in the respective map color for every allowed map color block and shade, so they can be matched
+/// like regular block textures.
+pub fn get_map_color_textures(texture_filtering_mode: &TextureFilteringMode, shades: &[usize]) -> HashMap<String, TextureWithBlockState> {
+    MAP_COLORS.iter()
+        .filter(|map_color| texture_filtering_mode.allows(map_color.block_id))
+        .flat_map(|map_color| shades.iter().map(move |&shade| (map_color, shade)))
+        .map(|(map_color, shade)| {
+            let [red, green, blue] = map_color.rgb.map(|channel| (channel as u32 * SHADE_MULTIPLIERS[shade] / 255) as u8);
+
+            (texture_name(map_color.id, shade), TextureWithBlockState {
+                texture: RgbaImage::from_pixel(16, 16, Rgba([red, green, blue, 255])).into(),
+                block_id: map_color.block_id.into(),
+                block_state_properties: map_color.block_state_properties.clone(),
+            })
+        })
+        .collect()
+}
+
+pub fn support_block() -> TextureWithBlockState {
+    TextureWithBlockState {
+        texture: RgbaImage::from_pixel(16, 16, Rgba([112, 112, 112, 255])).into(),
+        block_id: SUPPORT_BLOCK_ID.into(),
+        block_state_properties: None,
+    }
+}
+
+fn texture_name(map_color_id: u8, shade: usize) -> String {
+    format!("map_color_{map_color_id}_{shade}")
+}
+
+fn shade(texture_name: &str) -> usize {
+    texture_name.rsplit_once('_')
+        .and_then(|(_, shade)| shade.parse().ok())
+        .unwrap_or(FLAT_SHADE)
+}
+
+/// Lays the image out as a floor with the top of the image facing north, as it appears on a map. Each block is placed higher
+/// or lower than the block north of it depending on its shade, with a noob line in front of the image so the first row is
+/// shaded correctly as well. Carpets get a support block placed below them. The build has to fit the world height of the
+/// Minecraft version.
+pub fn floor_volume(output_blocks: &[Vec<String>], block_textures_and_states: &HashMap<String, TextureWithBlockState>, minecraft_version: &MinecraftVersion) -> eyre::Result<BlockVolume> {
+    let block_height = output_blocks.first().map(|column| column.len()).unwrap_or(0);
+
+    let heights_by_column = output_blocks.iter()
+        .map(|column| column_heights(&column.iter().map(|texture_name| shade(texture_name)).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+
+    // One additional layer for the support blocks below the lowest blocks
+    let volume_height = heights_by_column.iter().flatten().max().unwrap_or(&0) + 2;
+
+    if volume_height > minecraft_version.build_height() {
+        return Err(eyre!(
+            "The map art needs {volume_height} blocks of height, which exceeds the build height of {} blocks in Minecraft {}. Use flat map art or reduce the image's height.",
+            minecraft_version.build_height(),
+            minecraft_version.name
+        ));
+    }
+
+    let mut block_volume = BlockVolume::new([output_blocks.len(), volume_height, block_height + 1]);
+
+    for (x, (column, heights)) in output_blocks.iter().zip(&heights_by_column).enumerate() {
+        block_volume.set([x, heights[0] + 1, 0], SUPPORT_TEXTURE_NAME.into());
+
+        for (z, texture_name) in column.iter().enumerate() {
+            let position = [x, heights[z + 1] + 1, z + 1];
+
+            if block_textures_and_states[texture_name].block_id.ends_with("_carpet") {
+                block_volume.set([position[0], position[1] - 1, position[2]], SUPPORT_TEXTURE_NAME.into());
+            }
+
+            block_volume.set(position, texture_name.clone());
+        }
+    }
+
+    Ok(block_volume)
+}
+
+/// Heights of the noob line block and the blocks of a column from north to south, given the shade of each block. Heights are
+/// kept as low as possible with the lowest block at height 0.
+fn column_heights(shades: &[usize]) -> Vec<usize> {
+    // Split the column into segments which only ever rise, each starting with a block darker than the one north of it.
+    // Heights within a segment are relative to its start.
+    let mut segments = vec![vec![0]];
+
+    for &shade in shades {
+        if shade == DARKER_SHADE {
+            segments.push(vec![0]);
+        } else {
+            let segment = segments.last_mut().unwrap();
+            let previous_height = *segment.last().unwrap();
+
+            segment.push(if shade == LIGHTER_SHADE { previous_height + 1 } else { previous_height });
+        }
+    }
+
+    // Each segment has to end above the start of the following one
+    let mut segment_start_heights = vec![0usize; segments.len()];
+
+    for index in (0..segments.len() - 1).rev() {
+        segment_start_heights[index] = (segment_start_heights[index + 1] + 1).saturating_sub(*segments[index].last().unwrap());
+    }
+
+    segments.iter()
+        .zip(segment_start_heights)
+        .flat_map(|(segment, start_height)| segment.iter().map(move |height| start_height + height))
+        .collect()
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+use camino::Utf8Path;
+use color_eyre::eyre;
+
+use crate::helpers;
+
+/// Blocks that are always available, no matter the budget.
+const UNLIMITED_BLOCK_IDS: [&str; 1] = ["minecraft:air"];
+
+/// Number of blocks of each type that may still be placed, e.g. what is left in a storage system. Blocks that are not part
+/// of the budget are not available at all.
+pub struct MaterialBudget {
+    remaining_counts: HashMap<String, u64>,
+}
+
+impl MaterialBudget {
+    /// Reads a budget file with one `<block id>|<count>` line per block, counts of repeated blocks are added up.
+    pub fn load(material_budget_path: &Utf8Path) -> eyre::Result<Self> {
+        let mut remaining_counts = HashMap::new();
+
+        for (block_id, count) in helpers::read_block_values::<u64>(material_budget_path, "COUNT")? {
+            *remaining_counts.entry(block_id).or_default() += count;
+        }
+
+        Ok(Self { remaining_counts })
+    }
+
+    pub fn is_available(&self, block_id: &str) -> bool {
+        UNLIMITED_BLOCK_IDS.contains(&block_id) || self.remaining_counts.get(block_id).is_some_and(|&count| count > 0)
+    }
+
+    /// Uses up one block of the budget.
+    pub fn take(&mut self, block_id: &str) {
+        if let Some(count) = self.remaining_counts.get_mut(block_id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
\ No newline at end of file
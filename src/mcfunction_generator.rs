@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use color_eyre::eyre;
+use itertools::Itertools;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+use crate::cli_arguments::CliArguments;
+
+/// Maximum number of blocks a single `fill` command may change.
+const MAX_FILL_VOLUME: usize = 32768;
+
+const DATAPACK_NAMESPACE: &str = "img2mc";
+
+/// Returns either plain `.mcfunction` files or a datapack zip containing them, depending on the output path's extension.
+/// Blocks are placed relative to the position the functions are run from.
+pub fn make_files(
+    output_path: &Utf8Path,
+    cli_arguments: &CliArguments,
+    tint_biome: Option<&str>,
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<Vec<(Utf8PathBuf, Vec<u8>)>> {
+    let commands = make_commands(block_volume, block_textures_and_states);
+
+    // Function names may only contain lowercase letters, digits and a few special characters
+    let function_name = output_path.file_stem().unwrap_or("image")
+        .to_lowercase()
+        .chars()
+        .map(|character| if character.is_ascii_alphanumeric() || "_-.".contains(character) { character } else { '_' })
+        .collect::<String>();
+
+    // Every function stays under the command chain length limit so each of them can be run on its own
+    let header = tint_biome.map(|tint_biome| format!("# Colored for the {tint_biome} biome\n")).unwrap_or_default();
+
+    let functions = commands.chunks(cli_arguments.max_commands_per_function.max(1))
+        .map(|commands| header.clone() + &commands.join("\n") + "\n")
+        .collect::<Vec<_>>();
+
+    let function_names = if functions.len() == 1 {
+        vec![function_name]
+    } else {
+        (0..functions.len()).map(|index| format!("{function_name}_{index}")).collect()
+    };
+
+    if output_path.extension() == Some("zip") {
+        let mut zip_writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+        zip_writer.start_file("pack.mcmeta", FileOptions::default())?;
+        zip_writer.write_all(format!(
+            "{{\n    \"pack\": {{\n        \"pack_format\": {},\n        \"description\": \"Generated by img2mc\"\n    }}\n}}\n",
+            cli_arguments.minecraft_version.datapack_format
+        ).as_bytes())?;
+
+        for (function_name, function) in function_names.iter().zip(&functions) {
+            zip_writer.start_file(
+                format!("data/{DATAPACK_NAMESPACE}/{}/{function_name}.mcfunction", cli_arguments.minecraft_version.function_folder()),
+                FileOptions::default()
+            )?;
+            zip_writer.write_all(function.as_bytes())?;
+        }
+
+        tracing::info!(
+            "Run the following function(s) one after another to place the build: {}",
+            function_names.iter().map(|function_name| format!("/function {DATAPACK_NAMESPACE}:{function_name}")).join(", ")
+        );
+
+        Ok(vec![(output_path.to_path_buf(), zip_writer.finish()?.into_inner())])
+    } else {
+        Ok(
+            function_names.iter()
+                .zip(functions)
+                .map(|(function_name, function)| (output_path.with_file_name(format!("{function_name}.mcfunction")), function.into_bytes()))
+                .collect()
+        )
+    }
+}
+
+/// Creates `setblock` commands for single blocks and `fill` commands for runs of the same block along the X axis. Layers
+/// are placed from the bottom up, so blocks that need support have it by the time they are placed, even across functions.
+fn make_commands(
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> Vec<String> {
+    let [size_x, size_y, size_z] = block_volume.size;
+
+    let mut commands = vec![];
+
+    for y in 0..size_y {
+        for z in 0..size_z {
+            let mut run_start_x = 0;
+
+            while run_start_x < size_x {
+                let texture_name = block_volume.get([run_start_x, y, z]);
+
+                let run_length = (run_start_x..size_x)
+                    .take_while(|&x| block_volume.get([x, y, z]) == texture_name)
+                    .take(MAX_FILL_VOLUME)
+                    .count();
+
+                let block_state = block_textures_and_states[texture_name].block_state_string();
+
+                commands.push(if run_length == 1 {
+                    format!("setblock ~{run_start_x} ~{y} ~{z} {block_state}")
+                } else {
+                    format!("fill ~{run_start_x} ~{y} ~{z} ~{} ~{y} ~{z} {block_state}", run_start_x + run_length - 1)
+                });
+
+                run_start_x += run_length;
+            }
+        }
+    }
+
+    commands
+}
\ No newline at end of file
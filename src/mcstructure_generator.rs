@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre;
+use color_eyre::eyre::eyre;
+use fastnbt::Value;
+use itertools::Itertools;
+use once_cell::sync::Lazy;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+
+/// Block state version of Bedrock Edition 1.21.50, encoded as one byte per version component.
+const BEDROCK_BLOCK_VERSION: i32 = 1 << 24 | 21 << 16 | 50 << 8;
+
+/// Bedrock block names and state mappings by Java block ID, see `bedrock_blocks.txt`. Blocks without a Bedrock equivalent
+/// are listed with `-` so they fail with a proper error instead of an unknown block.
+static BEDROCK_BLOCKS: Lazy<HashMap<&'static str, Option<BedrockBlock>>> = Lazy::new(|| {
+    include_str!("bedrock_blocks.txt").lines()
+        .map(|line| match line.split('|').collect::<Vec<_>>()[..] {
+            [java_block_id, "-"] => (java_block_id, None),
+            [java_block_id, name] => (java_block_id, Some(BedrockBlock { name, state_mapping: None })),
+            [java_block_id, name, state_mapping] => (java_block_id, Some(BedrockBlock { name, state_mapping: Some(state_mapping) })),
+            _ => panic!("Invalid Bedrock block line '{}'.", line)
+        })
+        .collect()
+});
+
+struct BedrockBlock {
+    name: &'static str,
+    state_mapping: Option<&'static str>,
+}
+
+pub fn make_bytes(
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<Vec<u8>> {
+    // Unique list of all used textures as texture names
+    let used_block_textures = block_volume.texture_names()
+        .unique()
+        .collect::<Vec<_>>();
+
+    // Map of texture name to block palette index
+    let block_palette_by_texture = used_block_textures.iter()
+        .enumerate()
+        .map(|(index, &texture_name)| (texture_name, index))
+        .collect::<HashMap<_, _>>();
+
+    let block_palette = used_block_textures.iter()
+        .map(|&texture_name| to_bedrock_block_state(&block_textures_and_states[texture_name]))
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let [size_x, size_y, size_z] = block_volume.size;
+
+    // Blocks are ordered by X, then Y, then Z
+    let block_indices = (0..size_x)
+        .flat_map(|x| (0..size_y).flat_map(move |y| (0..size_z).map(move |z| [x, y, z])))
+        .map(|position| Value::Int(block_palette_by_texture[block_volume.get(position)] as i32))
+        .collect::<Vec<_>>();
+
+    let block_count = block_indices.len();
+
+    let structure = compound([
+        ("format_version", Value::Int(1)),
+        ("size", Value::List(block_volume.size.map(|size| Value::Int(size as i32)).into())),
+        ("structure", compound([
+            // The second layer holds waterlogging blocks, which are not used
+            ("block_indices", Value::List(vec![Value::List(block_indices), Value::List(vec![Value::Int(-1); block_count])])),
+            ("entities", Value::List(vec![])),
+            ("palette", compound([
+                ("default", compound([
+                    ("block_palette", Value::List(block_palette)),
+                    ("block_position_data", compound([])),
+                ])),
+            ])),
+        ])),
+        ("structure_world_origin", Value::List(vec![Value::Int(0); 3])),
+    ]);
+
+    let mut bytes = vec![];
+    write_little_endian_nbt(&mut bytes, "", &structure);
+
+    Ok(bytes)
+}
+
+fn compound<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Compound(entries.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+}
+
+/// Whether the block has a Bedrock Edition equivalent that can be written to a .mcstructure file.
+pub fn exists_on_bedrock(block_id: &str) -> bool {
+    BEDROCK_BLOCKS.get(block_id).is_some_and(Option::is_some)
+}
+
+fn to_bedrock_block_state(block: &TextureWithBlockState) -> eyre::Result<Value> {
+    let no_bedrock_block_error = || eyre!("Block '{}' does not exist on Bedrock Edition and cannot be written to a .mcstructure file. Exclude it from the palette, leave out all such blocks with --exclude-non-bedrock-blocks or use another output format.", block.block_state_string());
+
+    let bedrock_block = BEDROCK_BLOCKS.get(block.block_id.as_str())
+        .ok_or_else(|| eyre!("Block '{}' has no known Bedrock Edition equivalent and cannot be written to a .mcstructure file. Exclude it from the palette, leave out all such blocks with --exclude-non-bedrock-blocks or use another output format.", block.block_state_string()))?
+        .as_ref()
+        .ok_or_else(no_bedrock_block_error)?;
+
+    let property = |name: &str, default: &'static str| block.block_state_properties.as_ref()
+        .and_then(|block_state_properties| block_state_properties.get(name))
+        .map(|value| value.as_str())
+        .unwrap_or(default);
+
+    let bit = |name: &str| Value::Byte((property(name, "false") == "true") as i8);
+
+    // Both editions number six-way facing directions the same way
+    let facing_direction = |facing: &str| match facing {
+        "down" => 0,
+        "up" => 1,
+        "north" => 2,
+        "south" => 3,
+        "west" => 4,
+        _ => 5,
+    };
+
+    // Horizontal directions as used by legacy Bedrock `direction` states
+    let direction = |facing: &str| match facing {
+        "south" => 0,
+        "west" => 1,
+        "north" => 2,
+        _ => 3,
+    };
+
+    let mut name = bedrock_block.name.to_string();
+
+    let states: Vec<(&str, Value)> = match bedrock_block.state_mapping {
+        None => vec![],
+        Some("axis") => vec![("pillar_axis", Value::String(property("axis", "y").into()))],
+        Some("stairs") => vec![
+            ("weirdo_direction", Value::Int(match property("facing", "north") {
+                "east" => 0,
+                "west" => 1,
+                "south" => 2,
+                _ => 3,
+            })),
+            ("upside_down_bit", Value::Byte((property("half", "bottom") == "top") as i8)),
+        ],
+        Some("slab") => match property("type", "bottom") {
+            "double" => {
+                name = name.replace("_slab", "_double_slab");
+                vec![("minecraft:vertical_half", Value::String("bottom".into()))]
+            }
+            half => vec![("minecraft:vertical_half", Value::String(half.into()))],
+        },
+        Some("facing_direction") => vec![("facing_direction", Value::Int(facing_direction(property("facing", "north"))))],
+        Some("observer") => vec![("minecraft:facing_direction", Value::String(property("facing", "south").into()))],
+        Some("cardinal_direction") => vec![("minecraft:cardinal_direction", Value::String(property("facing", "south").into()))],
+        Some("piston") => vec![
+            // Bedrock pistons swap the horizontal directions
+            ("facing_direction", Value::Int(match property("facing", "north") {
+                "down" => 0,
+                "up" => 1,
+                "north" => 3,
+                "south" => 2,
+                "west" => 5,
+                _ => 4,
+            })),
+        ],
+        Some("trapdoor") => vec![
+            ("direction", Value::Int(match property("facing", "north") {
+                "east" => 0,
+                "west" => 1,
+                "south" => 2,
+                _ => 3,
+            })),
+            ("open_bit", bit("open")),
+            ("upside_down_bit", Value::Byte((property("half", "bottom") == "top") as i8)),
+        ],
+        Some("direction") => vec![("direction", Value::Int(direction(property("facing", "north"))))],
+        Some("fence_gate") => vec![
+            ("direction", Value::Int(direction(property("facing", "north")))),
+            ("open_bit", bit("open")),
+            ("in_wall_bit", bit("in_wall")),
+        ],
+        Some("wall") => {
+            // Bedrock calls low wall sides short
+            let connection_type = |side: &str| Value::String(match property(side, "none") {
+                "low" => "short".into(),
+                connection_type => connection_type.into(),
+            });
+
+            vec![
+                ("wall_connection_type_east", connection_type("east")),
+                ("wall_connection_type_north", connection_type("north")),
+                ("wall_connection_type_south", connection_type("south")),
+                ("wall_connection_type_west", connection_type("west")),
+                ("wall_post_bit", bit("up")),
+            ]
+        }
+        Some("pressure_plate") => vec![("redstone_signal", Value::Int(0))],
+        Some("chiseled_bookshelf") => vec![
+            ("direction", Value::Int(direction(property("facing", "north")))),
+            ("books_stored", Value::Int(
+                (0..6).filter(|slot| property(&format!("slot_{slot}_occupied"), "false") == "true").map(|slot| 1 << slot).sum()
+            )),
+        ],
+        Some("respawn_anchor") => vec![("respawn_anchor_charge", Value::Int(property("charges", "0").parse()?))],
+        Some("mushroom") => {
+            let sides = ["up", "down", "north", "south", "east", "west"].map(|side| property(side, "true"));
+
+            if sides.iter().all(|&side| side == "true") {
+                vec![("huge_mushroom_bits", Value::Int(14))]
+            } else if sides.iter().all(|&side| side == "false") {
+                vec![("huge_mushroom_bits", Value::Int(0))]
+            } else {
+                return Err(no_bedrock_block_error());
+            }
+        }
+        Some(state_mapping) => panic!("Unknown Bedrock state mapping '{}'.", state_mapping)
+    };
+
+    Ok(compound([
+        ("name", Value::String(name)),
+        ("states", Value::Compound(states.into_iter().map(|(name, value)| (name.to_string(), value)).collect())),
+        ("version", Value::Int(BEDROCK_BLOCK_VERSION)),
+    ]))
+}
+
+/// Bedrock Edition stores NBT in little endian, which fastnbt cannot write.
+fn write_little_endian_nbt(bytes: &mut Vec<u8>, name: &str, value: &Value) {
+    bytes.push(tag_id(value));
+    write_string(bytes, name);
+    write_payload(bytes, value);
+}
+
+fn tag_id(value: &Value) -> u8 {
+    match value {
+        Value::Byte(_) => 1,
+        Value::Short(_) => 2,
+        Value::Int(_) => 3,
+        Value::Long(_) => 4,
+        Value::Float(_) => 5,
+        Value::Double(_) => 6,
+        Value::ByteArray(_) => 7,
+        Value::String(_) => 8,
+        Value::List(_) => 9,
+        Value::Compound(_) => 10,
+        Value::IntArray(_) => 11,
+        Value::LongArray(_) => 12,
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    bytes.extend((string.len() as u16).to_le_bytes());
+    bytes.extend(string.as_bytes());
+}
+
+fn write_payload(bytes: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Byte(byte) => bytes.push(*byte as u8),
+        Value::Short(short) => bytes.extend(short.to_le_bytes()),
+        Value::Int(int) => bytes.extend(int.to_le_bytes()),
+        Value::Long(long) => bytes.extend(long.to_le_bytes()),
+        Value::Float(float) => bytes.extend(float.to_le_bytes()),
+        Value::Double(double) => bytes.extend(double.to_le_bytes()),
+        Value::String(string) => write_string(bytes, string),
+        Value::ByteArray(byte_array) => {
+            bytes.extend((byte_array.len() as i32).to_le_bytes());
+            bytes.extend(byte_array.iter().map(|&byte| byte as u8));
+        }
+        Value::IntArray(int_array) => {
+            bytes.extend((int_array.len() as i32).to_le_bytes());
+            bytes.extend(int_array.iter().flat_map(|int| int.to_le_bytes()));
+        }
+        Value::LongArray(long_array) => {
+            bytes.extend((long_array.len() as i32).to_le_bytes());
+            bytes.extend(long_array.iter().flat_map(|long| long.to_le_bytes()));
+        }
+        Value::List(list) => {
+            // Empty lists are written with the end tag as their element type
+            bytes.push(list.first().map(tag_id).unwrap_or(0));
+            bytes.extend((list.len() as i32).to_le_bytes());
+
+            for element in list {
+                write_payload(bytes, element);
+            }
+        }
+        Value::Compound(compound) => {
+            for (name, value) in compound.iter().sorted_by_key(|(name, _)| name.as_str()) {
+                write_little_endian_nbt(bytes, name, value);
+            }
+
+            bytes.push(0);
+        }
+    }
+}
\ No newline at end of file
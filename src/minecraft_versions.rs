@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+
+/// Java Edition releases that can be targeted with their data versions and datapack formats, see `minecraft_versions.txt`.
+static MINECRAFT_VERSIONS: Lazy<Vec<MinecraftVersion>> = Lazy::new(|| {
+    include_str!("minecraft_versions.txt").lines()
+        .map(|line| match line.split('|').collect::<Vec<_>>()[..] {
+            [name, data_version, datapack_format] => MinecraftVersion {
+                name,
+                data_version: data_version.parse().unwrap(),
+                datapack_format: datapack_format.parse().unwrap(),
+            },
+            _ => panic!("Invalid Minecraft version line '{}'.", line)
+        })
+        .collect()
+});
+
+/// Blocks added or renamed after Minecraft 1.16, see `block_versions.txt`. Blocks that are not listed exist in every
+/// supported version under the same name.
+static BLOCK_VERSIONS: Lazy<HashMap<&'static str, BlockVersion>> = Lazy::new(|| {
+    include_str!("block_versions.txt").lines()
+        .map(|line| {
+            let (block_id, version, previous_block_id) = match line.split('|').collect::<Vec<_>>()[..] {
+                [block_id, version] => (block_id, version, None),
+                [block_id, version, previous_block_id] => (block_id, version, Some(previous_block_id)),
+                _ => panic!("Invalid block version line '{}'.", line)
+            };
+
+            (block_id, BlockVersion {
+                version: version_number(version),
+                previous_block_id,
+            })
+        })
+        .collect()
+});
+
+#[derive(Clone)]
+pub struct MinecraftVersion {
+    pub name: &'static str,
+    pub data_version: i32,
+    /// `pack_format` of datapacks made for this version.
+    pub datapack_format: i32,
+}
+
+struct BlockVersion {
+    /// Version the block was added or renamed in.
+    version: [u32; 3],
+    /// Name of the block before it was renamed, `None` if it did not exist before.
+    previous_block_id: Option<&'static str>,
+}
+
+impl MinecraftVersion {
+    /// Name of a block in this version, e.g. `minecraft:grass_path` for `minecraft:dirt_path` before 1.17. Blocks renamed
+    /// since are accepted under their old name as well. `None` if the block does not exist in this version yet.
+    pub fn block_id(&self, block_id: &str) -> Option<String> {
+        let version = version_number(self.name);
+
+        if let Some((new_block_id, _)) = BLOCK_VERSIONS.iter()
+            .find(|(_, block_version)| block_version.previous_block_id == Some(block_id) && block_version.version <= version) {
+            return Some(new_block_id.to_string());
+        }
+
+        match BLOCK_VERSIONS.get(block_id) {
+            Some(block_version) if block_version.version > version => block_version.previous_block_id.map(|previous_block_id| previous_block_id.to_string()),
+            _ => Some(block_id.to_string()),
+        }
+    }
+
+    /// Whether worlds of this version store chunks in the format introduced with 1.18.
+    pub fn has_modern_chunk_format(&self) -> bool {
+        version_number(self.name) >= [1, 18, 0]
+    }
+
+    /// Number of blocks between the bottom and the top of the overworld, which grew with 1.18.
+    pub fn build_height(&self) -> usize {
+        if version_number(self.name) >= [1, 18, 0] { 384 } else { 256 }
+    }
+
+    /// Datapack folder holding the functions of a namespace, which lost its plural with 1.21.
+    pub fn function_folder(&self) -> &'static str {
+        if version_number(self.name) >= [1, 21, 0] { "function" } else { "functions" }
+    }
+}
+
+impl FromStr for MinecraftVersion {
+    type Err = &'static str;
+
+    /// Accepts exact versions like `1.20.1` as well as `1.20` or `1.20.x` for the latest known release of a minor version.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let minor_version = s.strip_suffix(".x").unwrap_or(s);
+        let is_minor_version = minor_version.matches('.').count() == 1;
+
+        MINECRAFT_VERSIONS.iter()
+            .find(|minecraft_version| minecraft_version.name == s)
+            .or_else(|| MINECRAFT_VERSIONS.iter()
+                .rfind(|minecraft_version| is_minor_version && version_number(minecraft_version.name)[..2] == version_number(minor_version)[..2]))
+            .cloned()
+            .ok_or("Invalid Minecraft version.")
+    }
+}
+
+/// Major, minor and patch number of a version like `1.20.1`, missing parts being 0.
+fn version_number(version: &str) -> [u32; 3] {
+    let mut parts = version.split('.').map(|part| part.parse::<u32>().unwrap_or(0));
+
+    [0; 3].map(|_| parts.next().unwrap_or(0))
+}
\ No newline at end of file
@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::ops::Neg;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks;
+use crate::blocks::{FaceTexture, TextureSource, TextureWithBlockState};
+use crate::cli_arguments::Orientation;
+
+/// Blocks whose `facing` property may also point up or down.
+const SIX_WAY_FACING_BLOCKS: [&str; 6] = [
+    "minecraft:barrel",
+    "minecraft:dispenser",
+    "minecraft:dropper",
+    "minecraft:observer",
+    "minecraft:piston",
+    "minecraft:sticky_piston",
+];
+
+const DIRECTIONS: [(&str, [i32; 3]); 6] = [
+    ("east", [1, 0, 0]),
+    ("west", [-1, 0, 0]),
+    ("up", [0, 1, 0]),
+    ("down", [0, -1, 0]),
+    ("south", [0, 0, 1]),
+    ("north", [0, 0, -1]),
+];
+
+const AXES: [(&str, [i32; 3]); 3] = [
+    ("x", [1, 0, 0]),
+    ("y", [0, 1, 0]),
+    ("z", [0, 0, 1]),
+];
+
+impl Orientation {
+    /// Rotates a vector from the default layout, a wall facing south, into this orientation.
+    pub fn rotate<T: Copy + Neg<Output = T>>(self, [x, y, z]: [T; 3]) -> [T; 3] {
+        match self {
+            Orientation::WallSouth => [x, y, z],
+            Orientation::WallNorth => [-x, y, -z],
+            Orientation::WallEast => [z, y, -x],
+            Orientation::WallWest => [-z, y, x],
+            Orientation::Floor => [x, z, -y],
+            Orientation::Ceiling => [-x, -z, -y],
+        }
+    }
+
+    /// Reverts [`Orientation::rotate`], turning a world vector into the viewer's frame with X pointing right, Y up and Z
+    /// towards the viewer.
+    pub fn unrotate<T: Copy + Neg<Output = T>>(self, [x, y, z]: [T; 3]) -> [T; 3] {
+        match self {
+            Orientation::WallSouth => [x, y, z],
+            Orientation::WallNorth => [-x, y, -z],
+            Orientation::WallEast => [-z, y, x],
+            Orientation::WallWest => [z, y, -x],
+            Orientation::Floor => [x, -z, y],
+            Orientation::Ceiling => [-x, -z, -y],
+        }
+    }
+
+    /// Direction from the build towards the viewer.
+    pub fn view_direction(self) -> [i32; 3] {
+        self.rotate([0, 0, 1])
+    }
+
+    /// Rotates the build from the default layout into this orientation, keeping all positions positive.
+    pub fn reorient_volume(self, block_volume: &BlockVolume) -> BlockVolume {
+        let rotated_size = self.rotate(block_volume.size.map(|size| size as i32)).map(|size| size.unsigned_abs() as usize);
+
+        // Axes that got flipped need to be offset so their positions start at 0 again
+        let rotated_far_corner = self.rotate(block_volume.size.map(|size| size as i32 - 1));
+        let offset = rotated_far_corner.map(|coordinate| (-coordinate).max(0));
+
+        let mut rotated_block_volume = BlockVolume::new(rotated_size);
+
+        for position in block_volume.positions() {
+            let rotated_position = self.rotate(position.map(|coordinate| coordinate as i32));
+
+            rotated_block_volume.set(
+                [0, 1, 2].map(|axis| (rotated_position[axis] + offset[axis]) as usize),
+                block_volume.get(position).clone()
+            );
+        }
+
+        rotated_block_volume
+    }
+
+    /// Rotates the directional block state properties of a block so its texture still faces the viewer in this orientation. If
+    /// the rotation cannot be expressed through the block state, the texture is replaced by the one of the face that ends up
+    /// facing the viewer instead. Returns `None` if the rotated block state cannot exist, e.g. stairs lying on their side, or
+    /// the texture of the visible face is unknown.
+    pub fn reorient_block(self, block: TextureWithBlockState, texture_source: &TextureSource) -> Option<TextureWithBlockState> {
+        let original_face = visible_face(block.block_state_properties.as_ref(), [0, 0, 1]);
+
+        let block = self.rotate_block_state(block)?;
+        let face = visible_face(block.block_state_properties.as_ref(), self.view_direction());
+
+        if face == original_face {
+            return Some(block);
+        }
+
+        match blocks::face_texture(&block.block_id, face) {
+            FaceTexture::Uniform => Some(block),
+            FaceTexture::Known(texture_name) => Some(TextureWithBlockState {
+                texture: texture_source.load_texture(texture_name)?,
+                ..block
+            }),
+            FaceTexture::Unknown => None,
+        }
+    }
+
+    fn rotate_block_state(self, block: TextureWithBlockState) -> Option<TextureWithBlockState> {
+        let Some(block_state_properties) = &block.block_state_properties else {
+            return Some(block);
+        };
+
+        let rotate_name = |names: &[(&'static str, [i32; 3])], name: &str, ignore_sign: bool| {
+            let (_, vector) = names.iter().find(|(existing_name, _)| *existing_name == name)?;
+            let rotated_vector = self.rotate(*vector);
+
+            names.iter()
+                .find(|(_, existing_vector)| {
+                    *existing_vector == rotated_vector || (ignore_sign && existing_vector.map(|coordinate| -coordinate) == rotated_vector)
+                })
+                .map(|(rotated_name, _)| *rotated_name)
+        };
+
+        let rotated_up = self.rotate([0, 1, 0]);
+
+        let mut rotated_block_state_properties = HashMap::new();
+
+        for (name, value) in block_state_properties {
+            let (rotated_name, rotated_value) = match name.as_str() {
+                "facing" => {
+                    let rotated_facing = rotate_name(&DIRECTIONS, value, false)?;
+
+                    if (rotated_facing == "up" || rotated_facing == "down") && !SIX_WAY_FACING_BLOCKS.contains(&block.block_id.as_str()) {
+                        return None;
+                    }
+
+                    (name.clone(), rotated_facing.to_string())
+                }
+                "axis" => (name.clone(), rotate_name(&AXES, value, true)?.to_string()),
+                // Top and bottom halves of stairs, slabs and trapdoors can only be flipped, not turned sideways
+                "half" | "type" if value == "top" || value == "bottom" => match rotated_up {
+                    [0, 1, 0] => (name.clone(), value.clone()),
+                    [0, -1, 0] => (name.clone(), if value == "top" { "bottom".into() } else { "top".into() }),
+                    _ => return None,
+                },
+                // Per-side properties such as those of mushroom blocks
+                side if DIRECTIONS.iter().any(|(direction, _)| *direction == side) => (rotate_name(&DIRECTIONS, side, false)?.to_string(), value.clone()),
+                _ => (name.clone(), value.clone()),
+            };
+
+            rotated_block_state_properties.insert(rotated_name, rotated_value);
+        }
+
+        Some(TextureWithBlockState {
+            block_state_properties: Some(rotated_block_state_properties),
+            ..block
+        })
+    }
+}
+
+/// Face of a block that is visible from the given direction, relative to the block's `facing` or `axis` property.
+fn visible_face(block_state_properties: Option<&HashMap<String, String>>, view_direction: [i32; 3]) -> &'static str {
+    let property_vector = |names: &[(&'static str, [i32; 3])], name: &str| block_state_properties
+        .and_then(|block_state_properties| block_state_properties.get(name))
+        .and_then(|value| names.iter().find(|(existing_name, _)| existing_name == value))
+        .map(|(_, vector)| *vector);
+
+    let opposite_view_direction = view_direction.map(|coordinate| -coordinate);
+
+    if let Some(facing) = property_vector(&DIRECTIONS, "facing") {
+        if view_direction == facing {
+            return "front";
+        } else if opposite_view_direction == facing {
+            return "back";
+        }
+    } else if let Some(axis) = property_vector(&AXES, "axis") {
+        return if view_direction == axis {
+            "top"
+        } else if opposite_view_direction == axis {
+            "bottom"
+        } else {
+            "side"
+        };
+    }
+
+    match view_direction {
+        [0, 1, 0] => "top",
+        [0, -1, 0] => "bottom",
+        _ => "side",
+    }
+}
\ No newline at end of file
@@ -0,0 +1,101 @@
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+
+use crate::{block_physics, helpers};
+
+/// Named groups of blocks to limit the palette to or to leave out of it, see `palette_presets.txt`. `no-gravity-blocks`
+/// leaves out the falling blocks of `block_physics.txt`.
+static PALETTE_PRESETS: Lazy<Vec<PalettePreset>> = Lazy::new(|| {
+    let no_gravity_blocks = PalettePreset {
+        name: "no-gravity-blocks",
+        only: false,
+        block_patterns: block_physics::falling_block_ids().map(BlockPattern::new).collect(),
+    };
+
+    include_str!("palette_presets.txt").lines()
+        .map(|line| {
+            let (name, only, block_patterns) = match line.split('|').collect::<Vec<_>>()[..] {
+                [name, "only", block_patterns] => (name, true, block_patterns),
+                [name, "exclude", block_patterns] => (name, false, block_patterns),
+                _ => panic!("Invalid palette preset line '{}'.", line)
+            };
+
+            PalettePreset {
+                name,
+                only,
+                block_patterns: block_patterns.split(',').map(BlockPattern::new).collect(),
+            }
+        })
+        .chain([no_gravity_blocks])
+        .collect()
+});
+
+#[derive(Clone)]
+pub struct PalettePreset {
+    pub name: &'static str,
+    /// Whether the palette is limited to the matching blocks instead of leaving them out.
+    only: bool,
+    block_patterns: Vec<BlockPattern>,
+}
+
+impl PalettePreset {
+    pub fn allows(&self, block_id: &str) -> bool {
+        self.block_patterns.iter().any(|block_pattern| block_pattern.matches(block_id)) == self.only
+    }
+}
+
+impl FromStr for PalettePreset {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PALETTE_PRESETS.iter()
+            .find(|palette_preset| palette_preset.name == s)
+            .cloned()
+            .ok_or("Invalid palette preset.")
+    }
+}
+
+/// Block ID where `*` stands for any number of characters and `?` for a single one, e.g. `*_glazed_terracotta`. The
+/// `minecraft:` namespace is implied if none is given.
+#[derive(Clone)]
+pub struct BlockPattern(String);
+
+impl BlockPattern {
+    pub fn new(pattern: &str) -> Self {
+        Self(helpers::namespaced_block_id(pattern))
+    }
+
+    pub fn matches(&self, block_id: &str) -> bool {
+        let pattern = self.0.chars().collect::<Vec<_>>();
+        let block_id = block_id.chars().collect::<Vec<_>>();
+
+        // Position after the last `*` in the pattern and the block ID position it was matched up to, to backtrack to
+        let mut backtrack_position = None;
+        let (mut pattern_index, mut block_id_index) = (0, 0);
+
+        while block_id_index < block_id.len() {
+            match pattern.get(pattern_index) {
+                Some('*') => {
+                    pattern_index += 1;
+                    backtrack_position = Some((pattern_index, block_id_index));
+                }
+                Some(&character) if character == '?' || character == block_id[block_id_index] => {
+                    pattern_index += 1;
+                    block_id_index += 1;
+                }
+                _ => match backtrack_position {
+                    // Let the last `*` cover one more character and try again from there
+                    Some((star_pattern_index, star_block_id_index)) => {
+                        pattern_index = star_pattern_index;
+                        block_id_index = star_block_id_index + 1;
+                        backtrack_position = Some((star_pattern_index, block_id_index));
+                    }
+                    None => return false,
+                },
+            }
+        }
+
+        pattern[pattern_index..].iter().all(|&character| character == '*')
+    }
+}
\ No newline at end of file
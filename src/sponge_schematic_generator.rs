@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::time;
+use std::time::SystemTime;
+
+use camino::Utf8Path;
+use color_eyre::eyre;
+use fastnbt::{ByteArray, IntArray};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+use crate::cli_arguments::{CliArguments, SpongeSchematicVersion};
+use crate::helpers;
+
+pub fn make_bytes(
+    output_path: &Utf8Path,
+    cli_arguments: &CliArguments,
+    tint_biome: Option<&str>,
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<Vec<u8>> {
+    // Unique list of all used block states in Sponge notation
+    let used_block_states = block_volume.texture_names()
+        .map(|texture_name| block_textures_and_states[texture_name].block_state_string())
+        .unique()
+        .collect::<Vec<_>>();
+
+    // Map of block state to palette index
+    let palette = used_block_states.iter()
+        .enumerate()
+        .map(|(index, block_state)| (block_state.clone(), index as i32))
+        .collect::<HashMap<_, _>>();
+
+    // Blocks are ordered by Y, then Z, then X
+    let mut block_data = Vec::new();
+
+    for texture_name in block_volume.texture_names() {
+        write_varint(&mut block_data, palette[&block_textures_and_states[texture_name].block_state_string()]);
+    }
+
+    let metadata = Metadata {
+        name: output_path.file_stem().unwrap_or("image").into(),
+        author: "img2mc".into(),
+        date: SystemTime::now().duration_since(time::UNIX_EPOCH)?.as_millis() as i64,
+        biome: tint_biome.map(|tint_biome| tint_biome.into()),
+    };
+
+    let nbt_bytes = match cli_arguments.sponge_schematic_version {
+        SpongeSchematicVersion::V2 => helpers::with_nbt_root_name(
+            fastnbt::to_bytes(&SchematicV2 {
+                version: 2,
+                data_version: cli_arguments.minecraft_version.data_version,
+                metadata,
+                width: block_volume.size[0] as u16 as i16,
+                height: block_volume.size[1] as u16 as i16,
+                length: block_volume.size[2] as u16 as i16,
+                offset: IntArray::new(vec![0, 0, 0]),
+                palette_max: palette.len() as i32,
+                palette,
+                block_data: ByteArray::new(block_data),
+                block_entities: vec![],
+            })?,
+            "Schematic"
+        ),
+        SpongeSchematicVersion::V3 => fastnbt::to_bytes(&SchematicV3Root {
+            schematic: SchematicV3 {
+                version: 3,
+                data_version: cli_arguments.minecraft_version.data_version,
+                metadata,
+                width: block_volume.size[0] as u16 as i16,
+                height: block_volume.size[1] as u16 as i16,
+                length: block_volume.size[2] as u16 as i16,
+                offset: IntArray::new(vec![0, 0, 0]),
+                blocks: BlockContainer {
+                    palette,
+                    data: ByteArray::new(block_data),
+                    block_entities: vec![],
+                },
+            }
+        })?,
+    };
+
+    Ok(helpers::gzip(&nbt_bytes)?)
+}
+
+fn write_varint(bytes: &mut Vec<i8>, value: i32) {
+    let mut value = value as u32;
+
+    while value >= 0b1000_0000 {
+        bytes.push(((value & 0b0111_1111) | 0b1000_0000) as u8 as i8);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8 as i8);
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SchematicV2 {
+    version: i32,
+    data_version: i32,
+    metadata: Metadata,
+    width: i16,
+    height: i16,
+    length: i16,
+    offset: IntArray,
+    palette_max: i32,
+    palette: HashMap<String, i32>,
+    block_data: ByteArray,
+    block_entities: Vec<EmptyObject>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SchematicV3Root {
+    schematic: SchematicV3,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SchematicV3 {
+    version: i32,
+    data_version: i32,
+    metadata: Metadata,
+    width: i16,
+    height: i16,
+    length: i16,
+    offset: IntArray,
+    blocks: BlockContainer,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct BlockContainer {
+    palette: HashMap<String, i32>,
+    data: ByteArray,
+    block_entities: Vec<EmptyObject>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Metadata {
+    name: String,
+    author: String,
+    date: i64,
+    /// Biome tinted blocks were colored for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    biome: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EmptyObject;
\ No newline at end of file
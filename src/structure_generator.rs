@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use color_eyre::eyre;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::block_volume::BlockVolume;
+use crate::blocks::TextureWithBlockState;
+use crate::helpers;
+
+/// Maximum size of a structure along each axis that a structure block is able to load.
+const MAX_STRUCTURE_SIZE: usize = 48;
+
+/// Returns the structure files to write, split into pieces a structure block can load if necessary, plus a manifest listing
+/// where to place each piece.
+pub fn make_files(
+    output_path: &Utf8Path,
+    data_version: i32,
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<Vec<(Utf8PathBuf, Vec<u8>)>> {
+    let size = block_volume.size;
+
+    if size.iter().all(|&length| length <= MAX_STRUCTURE_SIZE) {
+        return Ok(vec![(
+            output_path.to_path_buf(),
+            make_piece_bytes(data_version, [0, 0, 0], size, block_volume, block_textures_and_states)?
+        )]);
+    }
+
+    let file_stem = output_path.file_stem().unwrap_or("image");
+
+    let mut files = vec![];
+    let mut manifest = format!(
+        "Structure '{file_stem}' is {}x{}x{} blocks and was split into pieces of at most {MAX_STRUCTURE_SIZE}x{MAX_STRUCTURE_SIZE}x{MAX_STRUCTURE_SIZE} blocks.\n\
+        Load each piece with a structure block at the given offset (x y z) from the structure origin.\n\n",
+        size[0], size[1], size[2]
+    );
+
+    for piece_position in (0..size[0]).step_by(MAX_STRUCTURE_SIZE)
+        .cartesian_product((0..size[1]).step_by(MAX_STRUCTURE_SIZE))
+        .cartesian_product((0..size[2]).step_by(MAX_STRUCTURE_SIZE))
+        .map(|((x, y), z)| [x, y, z])
+    {
+        let piece_size = [0, 1, 2].map(|axis| (size[axis] - piece_position[axis]).min(MAX_STRUCTURE_SIZE));
+
+        let piece_path = output_path.with_file_name(format!(
+            "{file_stem}_{}_{}_{}.nbt",
+            piece_position[0] / MAX_STRUCTURE_SIZE,
+            piece_position[1] / MAX_STRUCTURE_SIZE,
+            piece_position[2] / MAX_STRUCTURE_SIZE
+        ));
+
+        manifest += &format!("{} {} {} {}\n", piece_path.file_name().unwrap_or_default(), piece_position[0], piece_position[1], piece_position[2]);
+
+        files.push((piece_path, make_piece_bytes(data_version, piece_position, piece_size, block_volume, block_textures_and_states)?));
+    }
+
+    files.push((output_path.with_file_name(format!("{file_stem}_manifest.txt")), manifest.into_bytes()));
+
+    Ok(files)
+}
+
+fn make_piece_bytes(
+    data_version: i32,
+    piece_position: [usize; 3],
+    piece_size: [usize; 3],
+    block_volume: &BlockVolume,
+    block_textures_and_states: &HashMap<String, TextureWithBlockState>
+) -> eyre::Result<Vec<u8>> {
+    // Texture name of each block in the piece with its position within the piece
+    let piece_blocks = (0..piece_size[0])
+        .cartesian_product(0..piece_size[1])
+        .cartesian_product(0..piece_size[2])
+        .map(|((x, y), z)| {
+            let position = [x, y, z];
+
+            (position.map(|coordinate| coordinate as i32), block_volume.get([0, 1, 2].map(|axis| piece_position[axis] + position[axis])))
+        })
+        .collect::<Vec<_>>();
+
+    // Unique list of all used textures as texture names
+    let used_block_textures = piece_blocks.iter()
+        .map(|(_, texture_name)| *texture_name)
+        .unique()
+        .collect::<Vec<_>>();
+
+    // Map of texture name to used block state palette index
+    let palette_index_by_texture = used_block_textures.iter()
+        .enumerate()
+        .map(|(index, &texture_name)| (texture_name, index as i32))
+        .collect::<HashMap<_, _>>();
+
+    Ok(helpers::gzip(&fastnbt::to_bytes(&Structure {
+        data_version,
+        size: piece_size.iter().map(|&length| length as i32).collect(),
+        palette: used_block_textures.iter()
+            .map(|&texture_name| PaletteEntry {
+                name: block_textures_and_states[texture_name].block_id.clone(),
+                properties: block_textures_and_states[texture_name].block_state_properties.clone(),
+            })
+            .collect(),
+        blocks: piece_blocks.into_iter()
+            .map(|(position, texture_name)| Block {
+                pos: position.to_vec(),
+                state: palette_index_by_texture[texture_name],
+            })
+            .collect(),
+        entities: vec![],
+    })?)?)
+}
+
+#[derive(Serialize)]
+struct Structure {
+    #[serde(rename = "DataVersion")]
+    data_version: i32,
+    size: Vec<i32>,
+    palette: Vec<PaletteEntry>,
+    blocks: Vec<Block>,
+    entities: Vec<EmptyObject>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct PaletteEntry {
+    name: String,
+    properties: Option<HashMap<String, String>>,
+}
+
+#[derive(Serialize)]
+struct Block {
+    pos: Vec<i32>,
+    state: i32,
+}
+
+#[derive(Serialize)]
+struct EmptyObject;
\ No newline at end of file